@@ -0,0 +1,219 @@
+//! Alert rules for balance changes.
+//!
+//! Without rules every balance change is reported. [`AlertRules`] narrow this
+//! down so that gas dust does not trigger alerts: a change is reported only
+//! if it is large enough (absolute or relative), goes in the wanted direction
+//! and leaves the balance outside the configured floor/ceiling band.
+//!
+//! [`FloorGuard`] is a separate low-balance alert that fires on crossing a
+//! floor, repeats while the balance stays below and reports the recovery.
+//!
+//! Amounts are in yoctoNEAR, or in the token's smallest unit for token
+//! watches.
+
+use serde::{Deserialize, Serialize};
+
+use crate::near::Direction;
+
+/// Conditions a balance change must meet to be reported.
+///
+/// All configured conditions must hold. Stored with each monitored account;
+/// entries persisted before rules existed deserialize as "any change".
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertRules {
+    /// Minimum absolute change since the last reported balance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_delta: Option<u128>,
+    /// Minimum change relative to the last reported balance, in percent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_percent: Option<f64>,
+    /// Only report changes leaving the balance below this amount.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<u128>,
+    /// Only report changes leaving the balance above this amount.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above: Option<u128>,
+    /// Only report increases ([`Direction::In`]) or decreases ([`Direction::Out`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+}
+
+/// What to do with a balance change, as decided by [`AlertRules::evaluate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Report the change and make the new balance the reference.
+    Alert,
+    /// Don't report the change, but make the new balance the reference.
+    Ignore,
+    /// Don't report the change and keep the old reference, so that small
+    /// changes add up until they reach the minimum delta.
+    Accumulate,
+}
+
+impl AlertRules {
+    /// Returns `true` if no rule is configured, i.e. every change is reported.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Decides whether the change from `previous` (the last reported
+    /// balance) to `current` should be reported.
+    pub fn evaluate(&self, previous: u128, current: u128) -> Verdict {
+        if previous == current {
+            return Verdict::Ignore;
+        }
+        let direction = if current > previous {
+            Direction::In
+        } else {
+            Direction::Out
+        };
+        if self.direction.is_some_and(|wanted| wanted != direction) || !self.in_band(current) {
+            return Verdict::Ignore;
+        }
+        let delta = current.abs_diff(previous);
+        if self.min_delta.is_some_and(|min| delta < min) {
+            return Verdict::Accumulate;
+        }
+        if let (Some(min), Some(percent)) = (self.min_percent, percent_change(previous, current)) {
+            if percent.abs() < min {
+                return Verdict::Accumulate;
+            }
+        }
+        Verdict::Alert
+    }
+
+    /// Returns `true` if `balance` is below the floor or above the ceiling,
+    /// or if neither is set.
+    fn in_band(&self, balance: u128) -> bool {
+        match (self.below, self.above) {
+            (None, None) => true,
+            (Some(below), None) => balance < below,
+            (None, Some(above)) => balance > above,
+            (Some(below), Some(above)) => balance < below || balance > above,
+        }
+    }
+
+    /// Describes the rules on one line, formatting amounts with `format`,
+    /// e.g. "change ≥ 1.0000 NEAR, below 10.0000 NEAR, outgoing only".
+    pub fn describe(&self, format: impl Fn(u128) -> String) -> String {
+        let mut parts = Vec::new();
+        if let Some(min) = self.min_delta {
+            parts.push(format!("change ≥ {}", format(min)));
+        }
+        if let Some(min) = self.min_percent {
+            parts.push(format!("change ≥ {min}%"));
+        }
+        if let Some(below) = self.below {
+            parts.push(format!("below {}", format(below)));
+        }
+        if let Some(above) = self.above {
+            parts.push(format!("above {}", format(above)));
+        }
+        match self.direction {
+            Some(Direction::In) => parts.push("incoming only".to_string()),
+            Some(Direction::Out) => parts.push("outgoing only".to_string()),
+            None => {}
+        }
+        if parts.is_empty() {
+            "any change".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Returns the signed change from `previous` to `current` in percent of
+/// `previous`, or `None` if `previous` is zero.
+pub fn percent_change(previous: u128, current: u128) -> Option<f64> {
+    if previous == 0 {
+        return None;
+    }
+    let percent = current.abs_diff(previous) as f64 / previous as f64 * 100.0;
+    Some(if current < previous {
+        -percent
+    } else {
+        percent
+    })
+}
+
+/// Low-balance guard for accounts that must not run dry, such as relayers.
+///
+/// Fires once when the balance drops below `floor`, reminds every
+/// `reminder_secs` while it stays below, and reports the recovery when it is
+/// topped up. The state is persisted with the monitored account, so a
+/// restart neither repeats nor forgets an alert.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FloorGuard {
+    /// Balance below which the guard fires.
+    pub floor: u128,
+    /// Seconds between reminders while the balance stays below; 0 disables
+    /// reminders.
+    pub reminder_secs: u64,
+    /// Unix time (seconds) the balance was first seen below the floor;
+    /// `None` while it is at or above.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below_since: Option<i64>,
+    /// Unix time (seconds) of the last low-balance alert or reminder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_alert: Option<i64>,
+}
+
+/// Transition reported by [`FloorGuard::check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FloorEvent {
+    /// The balance dropped below the floor.
+    Crossed,
+    /// The balance is still below the floor, since the given Unix time.
+    Reminder {
+        /// Unix time the balance dropped below the floor.
+        since: i64,
+    },
+    /// The balance is back at or above the floor.
+    Recovered,
+}
+
+impl FloorGuard {
+    /// Creates a guard for `floor`, reminding every `reminder_secs` (0 for never).
+    pub fn new(floor: u128, reminder_secs: u64) -> Self {
+        Self {
+            floor,
+            reminder_secs,
+            below_since: None,
+            last_alert: None,
+        }
+    }
+
+    /// Returns `true` while the balance is known to be below the floor.
+    pub fn is_below(&self) -> bool {
+        self.below_since.is_some()
+    }
+
+    /// Updates the state with the balance seen at Unix time `now` and returns
+    /// the event to report, if any.
+    pub fn check(&mut self, balance: u128, now: i64) -> Option<FloorEvent> {
+        match self.below_since {
+            None if balance < self.floor => {
+                self.below_since = Some(now);
+                self.last_alert = Some(now);
+                Some(FloorEvent::Crossed)
+            }
+            None => None,
+            Some(_) if balance >= self.floor => {
+                self.below_since = None;
+                self.last_alert = None;
+                Some(FloorEvent::Recovered)
+            }
+            Some(since) => {
+                let last_alert = self.last_alert.unwrap_or(since);
+                let due = self.reminder_secs > 0
+                    && now.saturating_sub(last_alert) >= self.reminder_secs as i64;
+                if due {
+                    self.last_alert = Some(now);
+                    Some(FloorEvent::Reminder { since })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,527 @@
+//! Exact NEAR amounts.
+//!
+//! [`NearAmount`] stores a yoctoNEAR value as `u128` and formats and parses
+//! it with integer arithmetic, so large balances are never rounded through
+//! `f64`. [`AmountFormat`] controls the unit, precision, trimming of
+//! trailing zeros and thousand separators.
+//!
+//! # Examples
+//!
+//! ```
+//! use near_balance_monitor::amount::{AmountFormat, NearAmount, Unit};
+//!
+//! let amount: NearAmount = "1234.5 NEAR".parse().unwrap();
+//! assert_eq!(amount.to_string(), "1234.5000 NEAR");
+//! assert_eq!(
+//!     amount.format(&AmountFormat::default().with_separators().trimmed()),
+//!     "1,234.5 NEAR"
+//! );
+//! assert_eq!(amount.format(&AmountFormat::default().with_unit(Unit::MilliNear)), "1234500.0000 mNEAR");
+//! ```
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Number of decimal places of NEAR: 1 NEAR = 10^24 yoctoNEAR.
+pub const NEAR_DECIMALS: u32 = 24;
+
+/// Units an amount can be shown in or parsed from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unit {
+    /// NEAR (10^24 yoctoNEAR).
+    #[default]
+    Near,
+    /// milliNEAR (10^21 yoctoNEAR).
+    MilliNear,
+    /// yoctoNEAR, the smallest unit.
+    Yocto,
+}
+
+impl Unit {
+    /// Number of decimal places of the unit relative to yoctoNEAR.
+    pub fn decimals(self) -> u32 {
+        match self {
+            Unit::Near => NEAR_DECIMALS,
+            Unit::MilliNear => NEAR_DECIMALS - 3,
+            Unit::Yocto => 0,
+        }
+    }
+
+    /// Symbol appended to formatted amounts.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Near => "NEAR",
+            Unit::MilliNear => "mNEAR",
+            Unit::Yocto => "yoctoNEAR",
+        }
+    }
+
+    /// Parses a unit symbol, ignoring case (`NEAR`, `mNEAR`, `yocto`, `yoctoNEAR`).
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        match symbol.to_ascii_lowercase().as_str() {
+            "near" | "Ⓝ" => Some(Unit::Near),
+            "mnear" | "millinear" => Some(Unit::MilliNear),
+            "yocto" | "yoctonear" => Some(Unit::Yocto),
+            _ => None,
+        }
+    }
+}
+
+/// How a [`NearAmount`] is formatted.
+///
+/// The default is NEAR with 4 decimal places, e.g. `1.5000 NEAR`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmountFormat {
+    /// Unit the amount is shown in.
+    pub unit: Unit,
+    /// Number of decimal places; the amount is rounded half up.
+    /// Capped at the number of decimals of the unit.
+    pub precision: u32,
+    /// Remove trailing zeros of the fraction (and the point if nothing is left).
+    pub trim: bool,
+    /// Group the integer part in thousands with `,`.
+    pub separators: bool,
+    /// Append the unit symbol.
+    pub symbol: bool,
+}
+
+impl Default for AmountFormat {
+    fn default() -> Self {
+        Self {
+            unit: Unit::Near,
+            precision: 4,
+            trim: false,
+            separators: false,
+            symbol: true,
+        }
+    }
+}
+
+impl AmountFormat {
+    /// Shows the amount in the given unit.
+    pub fn with_unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Rounds to the given number of decimal places.
+    pub fn with_precision(mut self, precision: u32) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Removes trailing zeros of the fraction.
+    pub fn trimmed(mut self) -> Self {
+        self.trim = true;
+        self
+    }
+
+    /// Groups the integer part in thousands.
+    pub fn with_separators(mut self) -> Self {
+        self.separators = true;
+        self
+    }
+
+    /// Omits the unit symbol, e.g. for CSV columns.
+    pub fn without_symbol(mut self) -> Self {
+        self.symbol = false;
+        self
+    }
+}
+
+/// An exact amount of NEAR, stored in yoctoNEAR.
+///
+/// Displays like `1.5000 NEAR` (see [`AmountFormat`]) and parses user input
+/// such as `1.5`, `1.5 NEAR`, `1,500 mNEAR` or `1000 yocto`. Serializes as a
+/// yoctoNEAR string, like the NEAR RPC; deserializes from such a string or
+/// from a JSON number, as sent by NearBlocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NearAmount(u128);
+
+impl NearAmount {
+    /// Creates an amount from yoctoNEAR.
+    pub const fn from_yocto(yocto: u128) -> Self {
+        NearAmount(yocto)
+    }
+
+    /// Returns the amount in yoctoNEAR.
+    pub const fn as_yocto(self) -> u128 {
+        self.0
+    }
+
+    /// Formats the amount.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::amount::{AmountFormat, NearAmount, Unit};
+    /// let amount = NearAmount::from_yocto(1_234_567_890_000_000_000_000_000_000);
+    /// assert_eq!(amount.to_string(), "1234.5679 NEAR");
+    /// assert_eq!(amount.format(&AmountFormat::default().with_precision(2).with_separators()), "1,234.57 NEAR");
+    /// assert_eq!(amount.format(&AmountFormat::default().with_unit(Unit::Yocto)), "1234567890000000000000000000 yoctoNEAR");
+    /// ```
+    pub fn format(self, format: &AmountFormat) -> String {
+        let number = format_decimal(
+            self.0,
+            format.unit.decimals(),
+            format.precision,
+            format.trim,
+            format.separators,
+        );
+        if format.symbol {
+            format!("{} {}", number, format.unit.symbol())
+        } else {
+            number
+        }
+    }
+}
+
+impl From<u128> for NearAmount {
+    fn from(yocto: u128) -> Self {
+        NearAmount(yocto)
+    }
+}
+
+impl fmt::Display for NearAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(&AmountFormat::default()))
+    }
+}
+
+impl FromStr for NearAmount {
+    type Err = String;
+
+    /// Parses an amount with an optional unit (NEAR if omitted).
+    ///
+    /// `,` is only accepted as a thousands separator in groups of three
+    /// digits before the decimal point, so a decimal comma such as `1,5` is
+    /// rejected rather than read as 15. `_` may separate digits anywhere.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::amount::NearAmount;
+    /// let parse = |s: &str| s.parse::<NearAmount>().map(NearAmount::as_yocto);
+    /// assert_eq!(parse("1.5"), Ok(1_500_000_000_000_000_000_000_000));
+    /// assert_eq!(parse("1.5 NEAR"), Ok(1_500_000_000_000_000_000_000_000));
+    /// assert_eq!(parse("1500 mNEAR"), Ok(1_500_000_000_000_000_000_000_000));
+    /// assert_eq!(parse("1_000 yocto"), Ok(1_000));
+    /// assert_eq!(parse("1,000.5"), Ok(1_000_500_000_000_000_000_000_000_000));
+    /// assert!(parse("1,5").is_err());
+    /// assert!(parse("1,2,3").is_err());
+    /// assert!(parse("0.5 yocto").is_err());
+    /// assert!(parse("-1").is_err());
+    /// ```
+    fn from_str(value: &str) -> Result<Self, String> {
+        let invalid =
+            || format!("invalid NEAR amount '{value}': expected e.g. 1.5, 1.5 NEAR or 1500 mNEAR");
+        let trimmed = value.trim();
+        let split = trimmed
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '_')))
+            .unwrap_or(trimmed.len());
+        let (number, symbol) = trimmed.split_at(split);
+        let symbol = symbol.trim();
+        let unit = if symbol.is_empty() {
+            Unit::Near
+        } else {
+            Unit::from_symbol(symbol).ok_or_else(invalid)?
+        };
+        let number = strip_separators(number).ok_or_else(invalid)?;
+        parse_decimal(&number, unit.decimals())
+            .map(NearAmount)
+            .ok_or_else(invalid)
+    }
+}
+
+impl Serialize for NearAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for NearAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NearAmountVisitor)
+    }
+}
+
+/// Accepts yoctoNEAR as a string or an integer, and floats such as `1.5e+24`
+/// as produced by JavaScript services.
+struct NearAmountVisitor;
+
+impl Visitor<'_> for NearAmountVisitor {
+    type Value = NearAmount;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a yoctoNEAR amount as a string or a number")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<NearAmount, E> {
+        value
+            .parse::<u128>()
+            .map(NearAmount)
+            .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<NearAmount, E> {
+        Ok(NearAmount(u128::from(value)))
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<NearAmount, E> {
+        Ok(NearAmount(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<NearAmount, E> {
+        u128::try_from(value)
+            .map(NearAmount)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<NearAmount, E> {
+        // `Display` prints the shortest decimal that round-trips, i.e. the
+        // digits the sender wrote, without an exponent; this keeps
+        // `1.5e+24` exact instead of rounding through the binary value.
+        let text = value.to_string();
+        let whole = text.split('.').next().unwrap_or_default();
+        if !value.is_finite() || value < 0.0 {
+            return Err(E::invalid_value(de::Unexpected::Float(value), &self));
+        }
+        whole
+            .parse::<u128>()
+            .map(NearAmount)
+            .map_err(|_| E::invalid_value(de::Unexpected::Float(value), &self))
+    }
+}
+
+/// Removes digit separators from a decimal number: `_` anywhere, and `,`
+/// only as a thousands separator of the whole part. Returns `None` for any
+/// other use of `,`.
+fn strip_separators(number: &str) -> Option<String> {
+    let number = number.replace('_', "");
+    let (whole, fraction) = number.split_once('.').unwrap_or((&number, ""));
+    if fraction.contains(',') {
+        return None;
+    }
+    if whole.contains(',') {
+        let mut groups = whole.split(',');
+        let first = groups.next().unwrap_or_default();
+        if first.is_empty() || first.len() > 3 || groups.any(|group| group.len() != 3) {
+            return None;
+        }
+    }
+    Some(number.replace(',', ""))
+}
+
+/// Parses a non-negative decimal number into an integer scaled by
+/// `10^decimals`. Returns `None` for malformed numbers, more fractional
+/// digits than `decimals`, or overflow, which includes any whole part
+/// other than 0 once `10^decimals` exceeds `u128`.
+///
+/// Shared by [`NearAmount`] parsing and fungible token amounts.
+pub fn parse_decimal(number: &str, decimals: u32) -> Option<u128> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > decimals as usize
+        || !is_digits(whole)
+        || !is_digits(fraction)
+    {
+        return None;
+    }
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    let fraction: u128 = if decimals == 0 {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = decimals as usize)
+            .parse()
+            .ok()?
+    };
+    if whole == 0 {
+        return Some(fraction);
+    }
+    whole
+        .checked_mul(10u128.checked_pow(decimals)?)?
+        .checked_add(fraction)
+}
+
+/// Formats an integer scaled by `10^decimals` as a decimal number with
+/// `precision` places, rounding half up.
+///
+/// Shared by [`NearAmount::format`] and fungible token formatting.
+pub fn format_decimal(
+    value: u128,
+    decimals: u32,
+    precision: u32,
+    trim: bool,
+    separators: bool,
+) -> String {
+    let shown = precision.min(decimals);
+    // A scale beyond `u128` is more than twice any value, which rounds to 0.
+    let rounded = match 10u128.checked_pow(decimals - shown) {
+        Some(scale) => {
+            let remainder = value % scale;
+            let rounded = value / scale;
+            if remainder >= scale - remainder {
+                rounded + 1
+            } else {
+                rounded
+            }
+        }
+        None => 0,
+    };
+    // Split the digits as text, since `10^shown` may not fit in `u128`.
+    let digits = format!("{:0>width$}", rounded, width = shown as usize + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - shown as usize);
+    let whole = if separators {
+        group_thousands(whole)
+    } else {
+        whole.to_string()
+    };
+    if shown == 0 {
+        return whole;
+    }
+    let fraction = if trim {
+        fraction.trim_end_matches('0')
+    } else {
+        fraction
+    };
+    if fraction.is_empty() {
+        whole
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+/// Inserts `,` between groups of three digits.
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One NEAR in yoctoNEAR.
+    const NEAR: u128 = 10u128.pow(NEAR_DECIMALS);
+
+    fn parse(value: &str) -> Result<u128, String> {
+        value.parse::<NearAmount>().map(NearAmount::as_yocto)
+    }
+
+    #[test]
+    fn parses_thousands_separators() {
+        assert_eq!(parse("1,000"), Ok(1_000 * NEAR));
+        assert_eq!(parse("1,234,567.5"), Ok(1_234_567 * NEAR + NEAR / 2));
+        assert_eq!(parse("1_000.000_1"), Ok(1_000 * NEAR + NEAR / 10_000));
+        for invalid in [
+            "1,5", "1,2,3", "1,0000", ",100", "1000,", "1.000,5", "1,,000",
+        ] {
+            assert!(parse(invalid).is_err(), "{invalid} should be rejected");
+        }
+    }
+
+    #[test]
+    fn parses_unit_suffixes() {
+        assert_eq!(parse("1.5"), Ok(3 * NEAR / 2));
+        assert_eq!(parse("1.5 NEAR"), Ok(3 * NEAR / 2));
+        assert_eq!(parse("1.5near"), Ok(3 * NEAR / 2));
+        assert_eq!(parse("2 Ⓝ"), Ok(2 * NEAR));
+        assert_eq!(parse("1500 mNEAR"), Ok(3 * NEAR / 2));
+        assert_eq!(parse("1 milliNEAR"), Ok(NEAR / 1_000));
+        assert_eq!(parse("1000 yocto"), Ok(1_000));
+        assert_eq!(parse("7 yoctoNEAR"), Ok(7));
+        for invalid in [
+            "",
+            "NEAR",
+            "1 foo",
+            "0.5 yocto",
+            "0.0000000000000000000001 mNEAR",
+            "-1",
+            "1e3",
+        ] {
+            assert!(parse(invalid).is_err(), "{invalid:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert!(parse("340282366920938.463463374607431768211456").is_err());
+        assert_eq!(parse(&format!("{} yocto", u128::MAX)), Ok(u128::MAX));
+    }
+
+    #[test]
+    fn formats_amounts() {
+        let amount = NearAmount::from_yocto(1_234_567 * NEAR + 891 * NEAR / 1_000);
+        assert_eq!(amount.to_string(), "1234567.8910 NEAR");
+        assert_eq!(
+            amount.format(&AmountFormat::default().with_separators().trimmed()),
+            "1,234,567.891 NEAR"
+        );
+        assert_eq!(
+            amount.format(&AmountFormat::default().with_precision(0).without_symbol()),
+            "1234568"
+        );
+        assert_eq!(
+            amount.format(
+                &AmountFormat::default()
+                    .with_unit(Unit::MilliNear)
+                    .with_precision(1)
+            ),
+            "1234567891.0 mNEAR"
+        );
+        assert_eq!(NearAmount::from_yocto(0).to_string(), "0.0000 NEAR");
+        assert_eq!(
+            NearAmount::from_yocto(NEAR / 20_000).to_string(),
+            "0.0001 NEAR",
+            "half of the last place rounds up"
+        );
+        assert_eq!(
+            NearAmount::from_yocto(1).format(&AmountFormat::default().trimmed()),
+            "0 NEAR"
+        );
+        assert_eq!(
+            NearAmount::from_yocto(u128::MAX)
+                .format(&AmountFormat::default().with_unit(Unit::Yocto)),
+            "340282366920938463463374607431768211455 yoctoNEAR"
+        );
+    }
+
+    #[test]
+    fn groups_thousands() {
+        assert_eq!(group_thousands("1"), "1");
+        assert_eq!(group_thousands("123"), "123");
+        assert_eq!(group_thousands("1234"), "1,234");
+        assert_eq!(group_thousands("1234567"), "1,234,567");
+    }
+
+    #[test]
+    fn handles_more_than_38_decimals() {
+        // 10^39 does not fit in u128: the value rounds to 0 at one place,
+        // and every u128 value is below 1.
+        assert_eq!(format_decimal(u128::MAX, 40, 1, false, false), "0.0");
+        assert_eq!(format_decimal(u128::MAX, 40, 4, false, false), "0.0340");
+        assert_eq!(format_decimal(10u128.pow(38), 40, 2, false, false), "0.01");
+        assert_eq!(format_decimal(10u128.pow(38), 40, 40, true, false), "0.01");
+        assert_eq!(
+            format_decimal(1, 40, 40, false, false),
+            format!("0.{}1", "0".repeat(39))
+        );
+        assert_eq!(parse_decimal("0.01", 40), Some(10u128.pow(38)));
+        assert_eq!(parse_decimal("0", 40), Some(0));
+        assert_eq!(parse_decimal("1", 40), None);
+        assert_eq!(parse_decimal("0.5", 40), None);
+    }
+}
@@ -17,26 +17,47 @@
 //!
 //! - `/start` - Welcome message
 //! - `/help` - Show available commands
-//! - `/balance <account>` - Query current balance
-//! - `/add <account>` - Add account to watchlist
-//! - `/remove <account>` - Remove account from watchlist
+//! - `/balance <account> [token_contract | date]` - Query current or historical balance
+//! - `/add <account> [token_contract | pool:<pool_id> | keys]` - Add account to watchlist
+//! - `/remove <account> [token_contract | pool:<pool_id> | keys]` - Remove account from watchlist
 //! - `/list` - List monitored accounts
-//! - `/trxs <account>` - Show recent transactions
+//! - `/trxs <account> [n]` - Show recent transactions, with a "next page" button
+//! - `/tx <hash> <signer>` - Show the status, receipts and logs of a transaction
+//! - `/staking <account> <pool>` - Show a staking pool delegation
+//! - `/fiat <currency | off>` - Show fiat values in balance alerts
+//! - `/alert <account> [token_contract] [key=value ...]` - Show or set alert rules
+//! - `/unalert <account> [token_contract]` - Alert on any change again
+//! - `/floor <account> [token_contract] <amount | off> [reminder]` - Low-balance guard
+//! - `/notify <account> [watch] <channel>` - Send alerts to Telegram, Slack, Discord or Matrix
+//!
+//! Alerts go through a [`Notifier`] chosen per watch (see [`crate::notify`]).
+//! With `--webhook`, balance changes are also POSTed to webhooks (see
+//! [`crate::webhook`]), once per change even if several chats watch the account.
 
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 use teloxide::utils::command::BotCommands;
 use tokio::sync::Mutex;
 use tokio::time;
 
-use crate::near::NearClient;
+use crate::alert::{self, AlertRules, FloorEvent, FloorGuard, Verdict};
+use crate::amount::{self, NEAR_DECIMALS, NearAmount};
+use crate::error::Error;
+use crate::near::{
+    self, AccessKey, AccessKeyPermission, BlockReference, Direction, NearClient, StakingBalance,
+    TransactionFilter, TransactionPage,
+};
+use crate::notify::{AlertChannel, MatrixConfig, Notifier, Notifiers};
 use crate::persistence::AccountPersistenceManager;
+use crate::price::PriceCache;
 use crate::utils;
+use crate::webhook::{WebhookNotifier, WebhookPayload};
 
 /// Telegram bot commands.
 ///
@@ -52,11 +73,17 @@ enum Command {
     Help,
     #[command(description = "start the bot.")]
     Start,
-    #[command(description = "fetch balance of an account. Usage: /balance <account_id>")]
+    #[command(
+        description = "fetch balance of an account, optionally at a past date. Usage: /balance <account_id> [token_contract | YYYY-MM-DD | RFC 3339 time]"
+    )]
     Balance(String),
-    #[command(description = "add an account to monitor.")]
+    #[command(
+        description = "add an account to monitor. Usage: /add <account_id> [token_contract | pool:<pool_id> | keys]"
+    )]
     Add(String),
-    #[command(description = "remove an account from monitoring.")]
+    #[command(
+        description = "remove an account from monitoring. Usage: /remove <account_id> [token_contract | pool:<pool_id> | keys]"
+    )]
     Remove(String),
     #[command(description = "remove an account from monitoring.")]
     Delete(String),
@@ -64,8 +91,38 @@ enum Command {
     Edit(String),
     #[command(description = "list monitored accounts.")]
     List,
-    #[command(description = "list last 10 transactions. Usage: /trxs <account_id>")]
+    #[command(
+        description = "list recent transactions (default 10, max 25). Usage: /trxs <account_id> [n]"
+    )]
     Trxs(String),
+    #[command(
+        description = "show the status, receipts and logs of a transaction. Usage: /tx <tx_hash> <signer_id>"
+    )]
+    Tx(String),
+    #[command(
+        description = "show a staking pool delegation. Usage: /staking <account_id> <pool_id>"
+    )]
+    Staking(String),
+    #[command(
+        description = "show fiat values in balance alerts. Usage: /fiat <currency | off>, e.g. /fiat usd"
+    )]
+    Fiat(String),
+    #[command(
+        description = "show or set alert rules. Usage: /alert <account_id> [token_contract] [min=<amount>] [pct=<percent>] [below=<amount>] [above=<amount>] [dir=in|out|any]"
+    )]
+    Alert(String),
+    #[command(
+        description = "alert on any balance change again. Usage: /unalert <account_id> [token_contract]"
+    )]
+    Unalert(String),
+    #[command(
+        description = "alert when a balance drops below a floor, with reminders until it recovers. Usage: /floor <account_id> [token_contract] <amount | off> [reminder, e.g. 1h | never]"
+    )]
+    Floor(String),
+    #[command(
+        description = "send alerts of an account to Telegram, Slack, Discord or Matrix. Usage: /notify <account_id> [token_contract | pool:<pool_id> | keys] <telegram | slack <webhook_url> | discord <webhook_url> | matrix <room_id>>"
+    )]
+    Notify(String),
 }
 
 /// Manages the persistence of user IDs to enable broadcasting and startup notifications.
@@ -139,17 +196,263 @@ impl UserManager {
     }
 }
 
+/// Per-chat preferences.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct ChatSettings {
+    /// Fiat currency shown next to NEAR amounts in balance alerts
+    /// (lowercase code, e.g. "usd"); `None` shows NEAR only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fiat: Option<String>,
+}
+
+/// Manages the persistence of per-chat preferences set with bot commands.
+///
+/// Settings are stored as a JSON object keyed by chat ID; chats without an
+/// entry use the defaults.
+struct ChatSettingsManager {
+    /// Settings by Telegram chat ID.
+    settings: HashMap<i64, ChatSettings>,
+    /// Path to the JSON file where settings are stored.
+    file_path: String,
+}
+
+impl ChatSettingsManager {
+    /// Loads chat settings from the specified file path.
+    ///
+    /// If the file does not exist, an empty `ChatSettingsManager` is returned.
+    fn load(file_path: &str) -> Self {
+        log::info!("Loading chat settings file={}", file_path);
+        let settings: HashMap<i64, ChatSettings> = if Path::new(file_path).exists() {
+            let data = fs::read_to_string(file_path).unwrap_or_default();
+            serde_json::from_str(&data).unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        log::info!(
+            "Chat settings loaded chat_count={} file={}",
+            settings.len(),
+            file_path
+        );
+        Self {
+            settings,
+            file_path: file_path.to_string(),
+        }
+    }
+
+    /// Returns the fiat currency chosen by a chat, if any.
+    fn fiat(&self, chat_id: ChatId) -> Option<String> {
+        self.settings
+            .get(&chat_id.0)
+            .and_then(|settings| settings.fiat.clone())
+    }
+
+    /// Sets (or with `None`, clears) the fiat currency of a chat and saves.
+    fn set_fiat(&mut self, chat_id: ChatId, fiat: Option<String>) {
+        log::info!("Fiat preference set chat_id={} fiat={:?}", chat_id.0, fiat);
+        self.settings.entry(chat_id.0).or_default().fiat = fiat;
+        self.save();
+    }
+
+    /// Saves the current settings to the configured file path.
+    fn save(&self) {
+        if let Ok(data) = serde_json::to_string(&self.settings) {
+            match fs::write(&self.file_path, data) {
+                Ok(_) => log::debug!(
+                    "Chat settings saved chat_count={} file={}",
+                    self.settings.len(),
+                    self.file_path
+                ),
+                Err(e) => log::error!(
+                    "Failed to save chat settings file={}: {}",
+                    self.file_path,
+                    e
+                ),
+            }
+        } else {
+            log::warn!("Failed to serialize chat settings");
+        }
+    }
+}
+
+/// Shared state passed to the command handler.
+#[derive(Clone)]
+struct BotState {
+    /// Monitored accounts of all chats.
+    monitored_accounts: Arc<Mutex<AccountPersistenceManager>>,
+    /// Known users.
+    user_manager: Arc<Mutex<UserManager>>,
+    /// Per-chat preferences.
+    chat_settings: Arc<Mutex<ChatSettingsManager>>,
+    /// Shared NEAR client.
+    near_client: Arc<NearClient>,
+    /// Fiat price source.
+    prices: Arc<PriceCache>,
+    /// Pending "next page" requests of `/trxs` listings.
+    tx_pages: Arc<Mutex<TxPages>>,
+    /// Alert channels, for testing `/notify` settings.
+    notifiers: Notifiers,
+}
+
+/// Default reminder interval of `/floor` guards: one hour.
+const DEFAULT_FLOOR_REMINDER_SECS: u64 = 3600;
+
+/// Maximum number of transactions listed in a balance change alert.
+const MAX_ALERT_TRANSACTIONS: usize = 5;
+
+/// Time limit for delivering one alert through a channel.
+const ALERT_SEND_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Default number of transactions per `/trxs` page.
+const DEFAULT_TX_PAGE_SIZE: usize = 10;
+
+/// Maximum number of transactions per `/trxs` page, keeping the reply below
+/// Telegram's message length limit.
+const MAX_TX_PAGE_SIZE: usize = 25;
+
+/// Number of `/trxs` pages whose "next page" button stays usable.
+const MAX_TX_PAGES: usize = 1000;
+
+/// Prefix of the callback data sent by "next page" buttons.
+const TX_PAGE_CALLBACK_PREFIX: &str = "trxs:";
+
+/// Parameters for fetching the next page of a `/trxs` listing.
+#[derive(Clone, Debug)]
+struct TxPageRequest {
+    /// Account whose transactions are listed.
+    account_id: String,
+    /// Number of transactions per page.
+    count: usize,
+    /// NearBlocks cursor where the next page starts.
+    cursor: String,
+}
+
+/// Pending "next page" requests of `/trxs` listings.
+///
+/// Telegram limits callback data to 64 bytes, which cannot hold an account
+/// ID and a cursor, so buttons carry a key into this map instead. Only the
+/// [`MAX_TX_PAGES`] most recent entries are kept; the map is not persisted.
+#[derive(Default)]
+struct TxPages {
+    /// Key of the next inserted request.
+    next_id: u64,
+    /// Requests by key, oldest first.
+    pages: BTreeMap<u64, TxPageRequest>,
+}
+
+impl TxPages {
+    /// Stores a request and returns its key, evicting the oldest entry if full.
+    fn insert(&mut self, request: TxPageRequest) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pages.insert(id, request);
+        if self.pages.len() > MAX_TX_PAGES {
+            self.pages.pop_first();
+        }
+        id
+    }
+
+    /// Returns the request stored under `id`, if it was not evicted.
+    fn get(&self, id: u64) -> Option<TxPageRequest> {
+        self.pages.get(&id).cloned()
+    }
+}
+
+/// What is being watched on a monitored account.
+///
+/// Stored with each [`MonitoredAccount`]; entries persisted before this field
+/// existed deserialize as [`WatchKind::Native`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WatchKind {
+    /// Native NEAR balance.
+    #[default]
+    Native,
+    /// NEP-141 fungible token balance held on the account.
+    Token {
+        /// Token contract ID (e.g., "usdt.tether-token.near").
+        contract: String,
+    },
+    /// Delegation to a staking pool: staked and unstaked balances.
+    Staking {
+        /// Staking pool account ID (e.g., "example.poolv1.near").
+        pool: String,
+    },
+    /// The account's access keys; alerts when a key is added or removed.
+    Keys,
+}
+
+impl WatchKind {
+    /// Parses the optional watch argument of bot commands:
+    /// `keys` for access keys, `pool:<pool_id>` for a staking pool,
+    /// otherwise a token contract.
+    fn from_arg(arg: Option<&str>) -> Self {
+        match arg {
+            Some("keys") => WatchKind::Keys,
+            Some(arg) => match arg.strip_prefix("pool:") {
+                Some(pool) => WatchKind::Staking {
+                    pool: pool.to_string(),
+                },
+                None => WatchKind::Token {
+                    contract: arg.to_string(),
+                },
+            },
+            None => WatchKind::Native,
+        }
+    }
+}
+
+impl std::fmt::Display for WatchKind {
+    /// Formats as a suffix for account IDs: empty for native, " (contract)" for
+    /// tokens, " (pool id)" for staking pools and " (access keys)" for keys.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WatchKind::Native => Ok(()),
+            WatchKind::Token { contract } => write!(f, " ({contract})"),
+            WatchKind::Staking { pool } => write!(f, " (pool {pool})"),
+            WatchKind::Keys => write!(f, " (access keys)"),
+        }
+    }
+}
+
 /// Internal state for an account being monitored by a specific user/chat.
 ///
-/// Each instance represents one account being watched by one user.
+/// Each instance represents one account (and one [`WatchKind`]) being watched by one user.
 /// The same account can be monitored by multiple users (multiple instances with different chat IDs).
 #[derive(Clone, Serialize, Deserialize)]
 pub struct MonitoredAccount {
     /// NEAR account ID being monitored (e.g., "example.near")
     pub account_id: String,
-    /// Last known balance in yoctoNEAR. Used to detect changes.
+    /// What is watched on the account: native NEAR or a fungible token.
+    #[serde(default)]
+    pub kind: WatchKind,
+    /// Last known balance in yoctoNEAR (or the token's smallest unit).
+    /// For staking pools this is the total held in the pool.
+    /// Used to detect changes.
     /// `None` means the initial balance hasn't been fetched yet.
     pub last_balance: Option<u128>,
+    /// Unix time (seconds) `last_balance` was recorded; alerts list the
+    /// transactions since then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_balance_at: Option<i64>,
+    /// Hashes of the transactions listed in the last balance alert, which
+    /// the next alert leaves out.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reported_transactions: Vec<String>,
+    /// Last known delegation state, for [`WatchKind::Staking`] entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_staking: Option<StakingBalance>,
+    /// Last known access keys, for [`WatchKind::Keys`] entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_keys: Option<Vec<AccessKey>>,
+    /// Which balance changes are reported, for native and token watches.
+    #[serde(default, skip_serializing_if = "AlertRules::is_empty")]
+    pub rules: AlertRules,
+    /// Low-balance guard and its state, for native and token watches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub floor: Option<FloorGuard>,
+    /// Where alerts are delivered; the chat itself by default.
+    #[serde(default, skip_serializing_if = "AlertChannel::is_telegram")]
+    pub channel: AlertChannel,
     /// The Telegram chat ID to send notifications to when balance changes.
     #[serde(
         serialize_with = "serialize_chat_id",
@@ -178,7 +481,15 @@ where
 /// Starts the Telegram bot and the background monitoring loop.
 ///
 /// This function initializes the bot, spawns a background task for monitoring
-/// account balances, and starts the command handler loop.
+/// account balances, and starts the command handler loop. The given client is
+/// shared by the background task and all command handlers.
+///
+/// # Arguments
+///
+/// * `near_client` - Client configured for the network to monitor
+/// * `prices` - Price source for fiat values in balance alerts
+/// * `webhook` - Webhooks notified of balance changes, if configured
+/// * `matrix` - Matrix account for `/notify ... matrix` channels, if configured
 ///
 /// # Environment Variables
 ///
@@ -186,11 +497,12 @@ where
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` when the bot stops gracefully, or an error message.
+/// Returns `Ok(())` when the bot stops gracefully, or an [`Error`].
 ///
 /// # Errors
 ///
-/// Returns `Err(String)` if the bot token is invalid or missing.
+/// Currently always returns `Ok(())`; a missing `TELOXIDE_TOKEN` panics
+/// inside teloxide before the bot starts.
 ///
 /// # Architecture
 ///
@@ -202,18 +514,35 @@ where
 ///
 /// ```no_run
 /// # use near_balance_monitor::bot;
+/// # use near_balance_monitor::near::NearClient;
+/// # use near_balance_monitor::network::NetworkConfig;
+/// # use near_balance_monitor::price::{CoinGeckoPrices, PriceCache, COINGECKO_API_URL};
+/// # use std::time::Duration;
 /// # #[tokio::main]
-/// # async fn main() -> Result<(), String> {
+/// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
 /// // Set TELOXIDE_TOKEN environment variable first
-/// bot::run().await?;
+/// let prices = PriceCache::new(
+///     Box::new(CoinGeckoPrices::new(COINGECKO_API_URL, None)),
+///     Duration::from_secs(300),
+/// );
+/// bot::run(NearClient::new(NetworkConfig::mainnet()), prices, None, None).await?;
 /// # Ok(())
 /// # }
 /// ```
-pub async fn run() -> Result<(), String> {
+pub async fn run(
+    near_client: NearClient,
+    prices: PriceCache,
+    webhook: Option<WebhookNotifier>,
+    matrix: Option<MatrixConfig>,
+) -> Result<(), Error> {
     log::info!("Starting bot...");
 
+    let near_client = Arc::new(near_client);
+    let prices = Arc::new(prices);
+
     let bot = Bot::from_env();
     log::info!("Bot initialized successfully");
+    let notifiers = Notifiers::new(bot.clone(), matrix);
 
     // Shared state: List of monitored accounts and known users
     let monitored_accounts: Arc<Mutex<AccountPersistenceManager>> = Arc::new(Mutex::new(
@@ -221,9 +550,15 @@ pub async fn run() -> Result<(), String> {
     ));
     let user_manager: Arc<Mutex<UserManager>> =
         Arc::new(Mutex::new(UserManager::load("users.json")));
+    let chat_settings: Arc<Mutex<ChatSettingsManager>> =
+        Arc::new(Mutex::new(ChatSettingsManager::load("chat_settings.json")));
 
     let monitored_accounts_for_loop = monitored_accounts.clone();
-    let bot_for_loop = bot.clone();
+    let chat_settings_for_loop = chat_settings.clone();
+    let prices_for_loop = prices.clone();
+    let notifiers_for_loop = notifiers.clone();
+    let near_client_for_loop = near_client.clone();
+    let webhook_for_shutdown = webhook.clone();
 
     // Notify users about new deployment/restart
     {
@@ -257,7 +592,7 @@ pub async fn run() -> Result<(), String> {
     // Spawn monitoring loop
     log::info!("Background monitoring task started interval=60s");
     tokio::spawn(async move {
-        let near_client = NearClient::new();
+        let near_client = near_client_for_loop;
         let mut interval = time::interval(Duration::from_secs(60)); // Check every minute
         let mut cycle_count: u64 = 0;
         let task_start = std::time::Instant::now();
@@ -278,55 +613,196 @@ pub async fn run() -> Result<(), String> {
                 cycle_count
             );
 
+            // Changes already sent to webhooks this cycle, as (account, kind,
+            // new balance), so that chats watching the same account do not
+            // repeat them.
+            let mut webhook_sent: HashSet<(String, String, u128)> = HashSet::new();
+
+            // The same account may be watched by several chats; fetch it once.
+            let mut account_ids: Vec<String> = Vec::new();
+            let mut token_pairs: Vec<(String, String)> = Vec::new();
+            let mut staking_pairs: Vec<(String, String)> = Vec::new();
+            let mut key_account_ids: Vec<String> = Vec::new();
+            for account in &accounts_to_check {
+                match &account.kind {
+                    WatchKind::Native => account_ids.push(account.account_id.clone()),
+                    WatchKind::Token { contract } => {
+                        token_pairs.push((contract.clone(), account.account_id.clone()))
+                    }
+                    WatchKind::Staking { pool } => {
+                        staking_pairs.push((pool.clone(), account.account_id.clone()))
+                    }
+                    WatchKind::Keys => key_account_ids.push(account.account_id.clone()),
+                }
+            }
+            let fetch_start = std::time::Instant::now();
+            let balances = near_client.fetch_balances(&account_ids).await;
+            let token_balances = near_client.fetch_token_balances(&token_pairs).await;
+            let staking_balances = near_client.fetch_staking_balances(&staking_pairs).await;
+            let key_lists = near_client.fetch_access_key_lists(&key_account_ids).await;
+            log::debug!(
+                "Fetched balances cycle={} unique_accounts={} unique_tokens={} unique_delegations={} unique_key_lists={} duration_ms={}",
+                cycle_count,
+                balances.len(),
+                token_balances.len(),
+                staking_balances.len(),
+                key_lists.len(),
+                fetch_start.elapsed().as_millis()
+            );
+
             for account in &accounts_to_check {
                 log::debug!(
-                    "Polling account={} chat_id={}",
+                    "Checking account={} chat_id={}",
                     account.account_id,
                     account.chat_id
                 );
-                match near_client.fetch_balance(&account.account_id).await {
-                    Ok(current_balance) => {
-                        let changed = account.last_balance != Some(current_balance);
-                        if changed {
-                            log::info!(
-                                "Balance change detected account={} chat_id={} old={:?} new={}",
-                                account.account_id,
-                                account.chat_id,
-                                account.last_balance,
-                                current_balance
-                            );
-                            let message = format!(
-                                "🚨 Balance Update for {}!\n\nOld: {}\nNew: {}",
+                let result = match &account.kind {
+                    WatchKind::Native => balances.get(&account.account_id),
+                    WatchKind::Token { contract } => {
+                        token_balances.get(&(contract.clone(), account.account_id.clone()))
+                    }
+                    WatchKind::Staking { pool } => {
+                        match staking_balances.get(&(pool.clone(), account.account_id.clone())) {
+                            Some(Ok(current)) => {
+                                check_staking(
+                                    &notifiers_for_loop,
+                                    &monitored_accounts_for_loop,
+                                    account,
+                                    pool,
+                                    current,
+                                )
+                                .await;
+                            }
+                            Some(Err(e)) => log::error!(
+                                "Error fetching staking for {}{} kind={}: {}",
                                 account.account_id,
-                                account
-                                    .last_balance
-                                    .map_or("Unknown".to_string(), utils::format_near),
-                                utils::format_near(current_balance)
-                            );
-
-                            if let Err(e) =
-                                bot_for_loop.send_message(account.chat_id, message).await
-                            {
-                                log::error!("Failed to send alert to {}: {}", account.chat_id, e);
+                                account.kind,
+                                e.kind(),
+                                e
+                            ),
+                            None => {}
+                        }
+                        continue;
+                    }
+                    WatchKind::Keys => {
+                        match key_lists.get(&account.account_id) {
+                            Some(Ok(current)) => {
+                                check_keys(
+                                    &notifiers_for_loop,
+                                    &monitored_accounts_for_loop,
+                                    account,
+                                    current,
+                                )
+                                .await;
                             }
-
-                            // Persist updated balance
-                            let mut guard = monitored_accounts_for_loop.lock().await;
-                            guard.update_balance(
-                                &account.account_id,
-                                account.chat_id,
-                                current_balance,
-                            );
-                            log::debug!(
-                                "Updated account state account={} chat_id={} balance={}",
+                            Some(Err(e)) => log::error!(
+                                "Error fetching access keys for {} kind={}: {}",
                                 account.account_id,
-                                account.chat_id,
-                                current_balance
-                            );
+                                e.kind(),
+                                e
+                            ),
+                            None => {}
+                        }
+                        continue;
+                    }
+                };
+                let Some(result) = result else {
+                    continue;
+                };
+                match result {
+                    Ok(current_balance) => {
+                        let current_balance = *current_balance;
+                        if let Some(floor) = &account.floor {
+                            check_floor(
+                                &notifiers_for_loop,
+                                &near_client,
+                                &monitored_accounts_for_loop,
+                                account,
+                                floor,
+                                current_balance,
+                            )
+                            .await;
                         }
+                        let verdict = match account.last_balance {
+                            Some(previous) if previous == current_balance => continue,
+                            Some(previous) => account.rules.evaluate(previous, current_balance),
+                            None => Verdict::Alert,
+                        };
+                        log::info!(
+                            "Balance change detected account={}{} chat_id={} old={:?} new={} verdict={:?}",
+                            account.account_id,
+                            account.kind,
+                            account.chat_id,
+                            account.last_balance,
+                            current_balance,
+                            verdict
+                        );
+                        let reported_transactions = match verdict {
+                            Verdict::Alert => {
+                                let transactions = send_balance_alert(
+                                    &notifiers_for_loop,
+                                    &near_client,
+                                    &chat_settings_for_loop,
+                                    &prices_for_loop,
+                                    account,
+                                    current_balance,
+                                )
+                                .await;
+                                if let (Some(webhook), Some(previous)) =
+                                    (&webhook, account.last_balance)
+                                {
+                                    let key = (
+                                        account.account_id.clone(),
+                                        account.kind.to_string(),
+                                        current_balance,
+                                    );
+                                    if webhook_sent.insert(key) {
+                                        let token = match &account.kind {
+                                            WatchKind::Token { contract } => {
+                                                Some(contract.as_str())
+                                            }
+                                            _ => None,
+                                        };
+                                        webhook.send(WebhookPayload::balance_change(
+                                            &account.account_id,
+                                            token,
+                                            previous,
+                                            current_balance,
+                                            transactions.clone(),
+                                        ));
+                                    }
+                                }
+                                transactions
+                            }
+                            Verdict::Ignore => Vec::new(),
+                            // Keep the last reported balance as the reference.
+                            Verdict::Accumulate => continue,
+                        };
+
+                        // Persist updated balance
+                        let mut guard = monitored_accounts_for_loop.lock().await;
+                        guard.update_balance(
+                            &account.account_id,
+                            &account.kind,
+                            account.chat_id,
+                            current_balance,
+                            reported_transactions,
+                        );
+                        log::debug!(
+                            "Updated account state account={} chat_id={} balance={}",
+                            account.account_id,
+                            account.chat_id,
+                            current_balance
+                        );
                     }
                     Err(e) => {
-                        log::error!("Error fetching balance for {}: {}", account.account_id, e);
+                        log::error!(
+                            "Error fetching balance for {}{} kind={}: {}",
+                            account.account_id,
+                            account.kind,
+                            e.kind(),
+                            e
+                        );
                     }
                 }
             }
@@ -343,13 +819,45 @@ pub async fn run() -> Result<(), String> {
     });
 
     log::info!("Command handler started, bot ready");
-    Command::repl(bot, move |bot, msg, cmd| {
-        let monitored_accounts = monitored_accounts.clone();
-        let user_manager = user_manager.clone();
-        async move { answer(bot, msg, cmd, monitored_accounts, user_manager).await }
-    })
-    .await;
+    let tx_pages: Arc<Mutex<TxPages>> = Arc::new(Mutex::new(TxPages::default()));
+    let near_client_for_callbacks = near_client.clone();
+    let tx_pages_for_callbacks = tx_pages.clone();
+    let state = BotState {
+        monitored_accounts,
+        user_manager,
+        chat_settings,
+        near_client,
+        prices,
+        tx_pages,
+        notifiers,
+    };
+    let handler = dptree::entry()
+        .branch(
+            Update::filter_message()
+                .filter_command::<Command>()
+                .endpoint(move |bot: Bot, msg: Message, cmd: Command| {
+                    let state = state.clone();
+                    async move { answer(bot, msg, cmd, state).await }
+                }),
+        )
+        .branch(
+            Update::filter_callback_query().endpoint(move |bot: Bot, query: CallbackQuery| {
+                let near_client = near_client_for_callbacks.clone();
+                let tx_pages = tx_pages_for_callbacks.clone();
+                async move { answer_callback(bot, query, near_client, tx_pages).await }
+            }),
+        );
+    Dispatcher::builder(bot, handler)
+        // Other updates (plain messages, edits, ...) are of no interest.
+        .default_handler(|_update| async {})
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
 
+    if let Some(webhook) = webhook_for_shutdown {
+        webhook.shutdown().await;
+    }
     Ok(())
 }
 
@@ -363,8 +871,8 @@ pub async fn run() -> Result<(), String> {
 /// * `bot` - The Telegram bot instance
 /// * `msg` - The incoming message containing the command
 /// * `cmd` - The parsed command enum
-/// * `monitored_accounts` - Shared state of monitored accounts
-/// * `user_manager` - Shared state of known users
+/// * `state` - Shared bot state: monitored accounts, users, chat settings,
+///   NEAR client, price source and pending `/trxs` pages
 ///
 /// # Returns
 ///
@@ -374,13 +882,16 @@ pub async fn run() -> Result<(), String> {
 ///
 /// Errors are caught and sent back to the user as error messages rather than
 /// propagated up, so the bot continues running even if individual commands fail.
-async fn answer(
-    bot: Bot,
-    msg: Message,
-    cmd: Command,
-    monitored_accounts: Arc<Mutex<AccountPersistenceManager>>,
-    user_manager: Arc<Mutex<UserManager>>,
-) -> ResponseResult<()> {
+async fn answer(bot: Bot, msg: Message, cmd: Command, state: BotState) -> ResponseResult<()> {
+    let BotState {
+        monitored_accounts,
+        user_manager,
+        chat_settings,
+        near_client,
+        prices,
+        tx_pages,
+        notifiers,
+    } = state;
     log::debug!(
         "Received message chat_id={} command={:?}",
         msg.chat.id.0,
@@ -427,17 +938,27 @@ async fn answer(
                 return Err(e);
             }
         }
-        Command::Balance(account_id) => {
+        Command::Balance(args) => {
+            let mut parts = args.split_whitespace();
+            let account_id = parts.next().unwrap_or_default().to_string();
+            let argument = parts.next();
+            let at = argument.and_then(|arg| utils::parse_datetime(arg).ok());
+            let kind = if at.is_some() {
+                WatchKind::Native
+            } else {
+                WatchKind::from_arg(argument)
+            };
             log::info!(
-                "Balance command chat_id={} account={}",
+                "Balance command chat_id={} account={}{}",
                 msg.chat.id.0,
-                account_id
+                account_id,
+                kind
             );
             if account_id.is_empty() {
                 if let Err(e) = bot
                     .send_message(
                         msg.chat.id,
-                        "Please provide an account ID. Usage: /balance <account_id>",
+                        "Please provide an account ID. Usage: /balance <account_id> [token_contract | YYYY-MM-DD]",
                     )
                     .await
                 {
@@ -451,26 +972,124 @@ async fn answer(
                 return Ok(());
             }
 
-            let near_client = NearClient::new();
-            match near_client.fetch_balance(&account_id).await {
-                Ok(balance) => {
+            if let Some(at) = at {
+                let block = BlockReference::Timestamp(at);
+                let result = match near_client.fetch_block(&block).await {
+                    Ok(header) => near_client
+                        .fetch_account_at(&account_id, &BlockReference::Height(header.height))
+                        .await
+                        .map(|account| (header, account)),
+                    Err(e) => Err(e),
+                };
+                let message = match result {
+                    Ok((header, account)) => {
+                        log::info!(
+                            "Historical balance command completed chat_id={} account={} at={} block={}",
+                            msg.chat.id.0,
+                            account_id,
+                            at,
+                            header.height
+                        );
+                        format!(
+                            "Balance for {} at {}: {}\n(block #{}, {})\n\n{}",
+                            account_id,
+                            at.format("%Y-%m-%d %H:%M:%S UTC"),
+                            utils::format_near(account.amount),
+                            header.height,
+                            utils::format_timestamp(header.timestamp.to_string()),
+                            utils::format_account_details(&account)
+                        )
+                    }
+                    Err(e) => {
+                        log::error!(
+                            "Historical balance command failed chat_id={} account={} at={} kind={}: {}",
+                            msg.chat.id.0,
+                            account_id,
+                            at,
+                            e.kind(),
+                            e
+                        );
+                        format!("Error fetching historical balance: {}", e.user_message())
+                    }
+                };
+                if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                    log::error!(
+                        "Failed to send Balance response chat_id={}: {}",
+                        msg.chat.id.0,
+                        e
+                    );
+                    return Err(e);
+                }
+                return Ok(());
+            }
+
+            if let WatchKind::Token { contract } = &kind {
+                let message = match near_client.ft_balance_of(contract, &account_id).await {
+                    Ok(balance) => format!(
+                        "Balance for {}{}: {}",
+                        account_id,
+                        kind,
+                        format_amount(&near_client, &kind, balance).await
+                    ),
+                    Err(e) => {
+                        log::error!(
+                            "Balance command failed chat_id={} account={}{} kind={}: {}",
+                            msg.chat.id.0,
+                            account_id,
+                            kind,
+                            e.kind(),
+                            e
+                        );
+                        format!("Error fetching token balance: {}", e.user_message())
+                    }
+                };
+                if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                    log::error!(
+                        "Failed to send Balance response chat_id={}: {}",
+                        msg.chat.id.0,
+                        e
+                    );
+                    return Err(e);
+                }
+                return Ok(());
+            }
+
+            match near_client.fetch_account(&account_id).await {
+                Ok(account) => {
                     log::info!(
                         "Balance command completed chat_id={} account={} balance={}",
                         msg.chat.id.0,
                         account_id,
-                        balance
+                        account.amount
                     );
-                    if let Err(e) = bot
-                        .send_message(
-                            msg.chat.id,
-                            format!(
-                                "Balance for {}: {}",
-                                account_id,
-                                utils::format_near(balance)
-                            ),
-                        )
-                        .await
-                    {
+                    let mut response = format!(
+                        "Balance for {}: {}\n\n{}",
+                        account_id,
+                        utils::format_near(account.amount),
+                        utils::format_account_details(&account)
+                    );
+                    if near::is_lockup_account(&account_id) {
+                        match near_client.fetch_lockup(&account_id).await {
+                            Ok(lockup) => response.push_str(&format!(
+                                "\n\nLockup:\n{}",
+                                utils::format_lockup_details(&lockup)
+                            )),
+                            Err(e) => {
+                                log::warn!(
+                                    "Balance command: lockup fetch failed chat_id={} account={} kind={}: {}",
+                                    msg.chat.id.0,
+                                    account_id,
+                                    e.kind(),
+                                    e
+                                );
+                                response.push_str(&format!(
+                                    "\n\nLockup details unavailable: {}",
+                                    e.user_message()
+                                ));
+                            }
+                        }
+                    }
+                    if let Err(e) = bot.send_message(msg.chat.id, response).await {
                         log::error!(
                             "Failed to send Balance success response chat_id={}: {}",
                             msg.chat.id.0,
@@ -481,13 +1100,17 @@ async fn answer(
                 }
                 Err(e) => {
                     log::error!(
-                        "Balance command failed chat_id={} account={}: {}",
+                        "Balance command failed chat_id={} account={} kind={}: {}",
                         msg.chat.id.0,
                         account_id,
+                        e.kind(),
                         e
                     );
                     if let Err(send_err) = bot
-                        .send_message(msg.chat.id, format!("Error fetching balance: {}", e))
+                        .send_message(
+                            msg.chat.id,
+                            format!("Error fetching balance: {}", e.user_message()),
+                        )
                         .await
                     {
                         log::error!(
@@ -500,11 +1123,15 @@ async fn answer(
                 }
             }
         }
-        Command::Add(account_id) => {
+        Command::Add(args) => {
+            let mut parts = args.split_whitespace();
+            let account_id = parts.next().unwrap_or_default().to_string();
+            let kind = WatchKind::from_arg(parts.next());
             log::info!(
-                "Add command chat_id={} account={}",
+                "Add command chat_id={} account={}{}",
                 msg.chat.id.0,
-                account_id
+                account_id,
+                kind
             );
             if account_id.is_empty() {
                 if let Err(e) = bot
@@ -521,23 +1148,127 @@ async fn answer(
                 return Ok(());
             }
 
+            // Reject accounts that don't exist; other errors shouldn't block adding.
+            if let Err(e) = near_client.fetch_balance(&account_id).await {
+                if e.is_account_not_found() {
+                    log::warn!(
+                        "Add command: account does not exist chat_id={} account={}",
+                        msg.chat.id.0,
+                        account_id
+                    );
+                    if let Err(send_err) = bot
+                        .send_message(
+                            msg.chat.id,
+                            format!("{} doesn't exist on this network.", account_id),
+                        )
+                        .await
+                    {
+                        log::error!(
+                            "Failed to send Add not found response chat_id={}: {}",
+                            msg.chat.id.0,
+                            send_err
+                        );
+                        return Err(send_err);
+                    }
+                    return Ok(());
+                }
+                log::warn!(
+                    "Add command: could not verify account chat_id={} account={} kind={}: {}",
+                    msg.chat.id.0,
+                    account_id,
+                    e.kind(),
+                    e
+                );
+            }
+
+            // Make sure token contracts actually implement NEP-141 metadata.
+            if let WatchKind::Token { contract } = &kind {
+                if let Err(e) = near_client.ft_metadata(contract).await {
+                    log::warn!(
+                        "Add command: invalid token contract chat_id={} token={} kind={}: {}",
+                        msg.chat.id.0,
+                        contract,
+                        e.kind(),
+                        e
+                    );
+                    let reply = if e.is_account_not_found() {
+                        format!("Token contract {} doesn't exist on this network.", contract)
+                    } else {
+                        format!(
+                            "Could not read token metadata from {}: {}",
+                            contract,
+                            e.user_message()
+                        )
+                    };
+                    if let Err(send_err) = bot.send_message(msg.chat.id, reply).await {
+                        log::error!(
+                            "Failed to send Add token error response chat_id={}: {}",
+                            msg.chat.id.0,
+                            send_err
+                        );
+                        return Err(send_err);
+                    }
+                    return Ok(());
+                }
+            }
+
+            // Make sure the pool implements the staking-pool view methods.
+            if let WatchKind::Staking { pool } = &kind {
+                if let Err(e) = near_client.fetch_staking(pool, &account_id).await {
+                    log::warn!(
+                        "Add command: invalid staking pool chat_id={} pool={} kind={}: {}",
+                        msg.chat.id.0,
+                        pool,
+                        e.kind(),
+                        e
+                    );
+                    let reply = if e.is_account_not_found() {
+                        format!("Staking pool {} doesn't exist on this network.", pool)
+                    } else {
+                        format!(
+                            "Could not read staking balances from {}: {}",
+                            pool,
+                            e.user_message()
+                        )
+                    };
+                    if let Err(send_err) = bot.send_message(msg.chat.id, reply).await {
+                        log::error!(
+                            "Failed to send Add pool error response chat_id={}: {}",
+                            msg.chat.id.0,
+                            send_err
+                        );
+                        return Err(send_err);
+                    }
+                    return Ok(());
+                }
+            }
+
             let mut guard = monitored_accounts.lock().await;
             let account = MonitoredAccount {
                 account_id: account_id.clone(),
+                kind: kind.clone(),
                 last_balance: None,
+                last_balance_at: None,
+                reported_transactions: Vec::new(),
+                last_staking: None,
+                last_keys: None,
+                rules: AlertRules::default(),
+                floor: None,
+                channel: AlertChannel::default(),
                 chat_id: msg.chat.id,
             };
 
             if guard.add_account(account) {
                 log::info!(
-                    "Account added to monitoring chat_id={} account={}",
+                    "Account added to monitoring chat_id={} account={}{}",
                     msg.chat.id.0,
-                    account_id
+                    account_id,
+                    kind
                 );
                 if let Err(e) = bot
                     .send_message(
                         msg.chat.id,
-                        format!("Added {} to monitoring list.", account_id),
+                        format!("Added {}{} to monitoring list.", account_id, kind),
                     )
                     .await
                 {
@@ -550,14 +1281,15 @@ async fn answer(
                 }
             } else {
                 log::warn!(
-                    "Add command: already monitored chat_id={} account={}",
+                    "Add command: already monitored chat_id={} account={}{}",
                     msg.chat.id.0,
-                    account_id
+                    account_id,
+                    kind
                 );
                 if let Err(e) = bot
                     .send_message(
                         msg.chat.id,
-                        format!("{} is already being monitored.", account_id),
+                        format!("{}{} is already being monitored.", account_id, kind),
                     )
                     .await
                 {
@@ -570,24 +1302,29 @@ async fn answer(
                 }
             }
         }
-        Command::Remove(account_id) | Command::Delete(account_id) => {
+        Command::Remove(args) | Command::Delete(args) => {
+            let mut parts = args.split_whitespace();
+            let account_id = parts.next().unwrap_or_default().to_string();
+            let kind = WatchKind::from_arg(parts.next());
             log::info!(
-                "Remove command chat_id={} account={}",
+                "Remove command chat_id={} account={}{}",
                 msg.chat.id.0,
-                account_id
+                account_id,
+                kind
             );
             let mut guard = monitored_accounts.lock().await;
 
-            if guard.remove_account(&account_id, msg.chat.id) {
+            if guard.remove_account(&account_id, &kind, msg.chat.id) {
                 log::info!(
-                    "Account removed chat_id={} account={}",
+                    "Account removed chat_id={} account={}{}",
                     msg.chat.id.0,
-                    account_id
+                    account_id,
+                    kind
                 );
                 if let Err(e) = bot
                     .send_message(
                         msg.chat.id,
-                        format!("Removed {} from monitoring list.", account_id),
+                        format!("Removed {}{} from monitoring list.", account_id, kind),
                     )
                     .await
                 {
@@ -607,7 +1344,7 @@ async fn answer(
                 if let Err(e) = bot
                     .send_message(
                         msg.chat.id,
-                        format!("Account {} was not found.", account_id),
+                        format!("Account {}{} was not found.", account_id, kind),
                     )
                     .await
                 {
@@ -683,18 +1420,39 @@ async fn answer(
         }
         Command::List => {
             let guard = monitored_accounts.lock().await;
-            let accounts: Vec<String> = guard
+            let entries: Vec<MonitoredAccount> = guard
                 .get_accounts_for_chat(msg.chat.id)
-                .iter()
-                .map(|acc| acc.account_id.clone())
+                .into_iter()
+                .cloned()
                 .collect();
             log::info!(
                 "List command chat_id={} account_count={}",
                 msg.chat.id.0,
-                accounts.len()
+                entries.len()
             );
             drop(guard); // Explicitly drop mutex guard before sending message
 
+            let mut accounts = Vec::new();
+            for acc in &entries {
+                let mut line = format!("{}{}", acc.account_id, acc.kind);
+                if !acc.rules.is_empty() {
+                    line.push_str(&format!(
+                        " — alerts: {}",
+                        describe_rules(&near_client, acc).await
+                    ));
+                }
+                if acc.floor.is_some() {
+                    line.push_str(&format!(
+                        " — floor: {}",
+                        describe_floor(&near_client, acc).await
+                    ));
+                }
+                if !acc.channel.is_telegram() {
+                    line.push_str(&format!(" — alerts to {}", acc.channel));
+                }
+                accounts.push(line);
+            }
+
             if accounts.is_empty() {
                 if let Err(e) = bot
                     .send_message(msg.chat.id, "You are not monitoring any accounts.")
@@ -722,12 +1480,22 @@ async fn answer(
                 }
             }
         }
-        Command::Trxs(account_id) => {
-            if account_id.is_empty() {
+        Command::Trxs(args) => {
+            let mut parts = args.split_whitespace();
+            let account_id = parts.next().unwrap_or_default().to_string();
+            let count = match parts.next().map(str::parse::<usize>) {
+                None => Some(DEFAULT_TX_PAGE_SIZE),
+                Some(Ok(count)) if (1..=MAX_TX_PAGE_SIZE).contains(&count) => Some(count),
+                Some(_) => None,
+            };
+            let Some(count) = count.filter(|_| !account_id.is_empty()) else {
                 if let Err(e) = bot
                     .send_message(
                         msg.chat.id,
-                        "Please provide an account ID. Usage: /trxs <account_id>",
+                        format!(
+                            "Please provide an account ID. Usage: /trxs <account_id> [n], with n up to {}",
+                            MAX_TX_PAGE_SIZE
+                        ),
                     )
                     .await
                 {
@@ -739,63 +1507,1294 @@ async fn answer(
                     return Err(e);
                 }
                 return Ok(());
-            }
-
-            let near_client = NearClient::new();
-            match near_client.fetch_transactions(&account_id).await {
-                Ok(txs) => {
-                    if txs.is_empty() {
-                        if let Err(e) = bot
-                            .send_message(
-                                msg.chat.id,
-                                format!("No transactions found for {}.", account_id),
-                            )
-                            .await
-                        {
-                            log::error!(
-                                "Failed to send Trxs empty response chat_id={}: {}",
-                                msg.chat.id.0,
-                                e
-                            );
-                            return Err(e);
-                        }
-                    } else {
-                        let mut response = format!("Last 10 transactions for {}:\n", account_id);
-                        for tx in txs {
-                            response.push_str(&format!(
-                                "\nTime: {}\nHash: {}...\nFrom: {}\nTo: {}\nAmount: {}\n",
-                                utils::format_timestamp(tx.block_timestamp),
-                                &tx.hash[..10],
-                                tx.signer_id,
-                                tx.receiver_id,
-                                utils::format_near(tx.actions_agg.deposit as u128)
-                            ));
-                        }
-                        if let Err(e) = bot.send_message(msg.chat.id, response).await {
-                            log::error!(
-                                "Failed to send Trxs success response chat_id={}: {}",
-                                msg.chat.id.0,
-                                e
-                            );
-                            return Err(e);
-                        }
-                    }
+            };
+            log::info!(
+                "Trxs command chat_id={} account={} count={}",
+                msg.chat.id.0,
+                account_id,
+                count
+            );
+            send_transactions(
+                &bot,
+                msg.chat.id,
+                &near_client,
+                &tx_pages,
+                TxPageRequest {
+                    account_id,
+                    count,
+                    cursor: String::new(),
+                },
+            )
+            .await?;
+        }
+        Command::Tx(args) => {
+            log::info!("Tx command chat_id={} args={}", msg.chat.id.0, args);
+            let parts: Vec<&str> = args.split_whitespace().collect();
+            if parts.len() != 2 {
+                if let Err(e) = bot
+                    .send_message(msg.chat.id, "Usage: /tx <tx_hash> <signer_id>")
+                    .await
+                {
+                    log::error!(
+                        "Failed to send Tx validation error chat_id={}: {}",
+                        msg.chat.id.0,
+                        e
+                    );
+                    return Err(e);
                 }
+                return Ok(());
+            }
+            let (tx_hash, signer_id) = (parts[0], parts[1]);
+
+            let message = match near_client
+                .fetch_transaction_status(tx_hash, signer_id)
+                .await
+            {
+                Ok(status) => truncate_message(utils::format_transaction_status(&status)),
                 Err(e) => {
-                    if let Err(send_err) = bot
-                        .send_message(msg.chat.id, format!("Error fetching transactions: {}", e))
-                        .await
-                    {
-                        log::error!(
-                            "Failed to send Trxs error response chat_id={}: {}",
+                    log::error!(
+                        "Tx command failed chat_id={} hash={} signer={} kind={}: {}",
+                        msg.chat.id.0,
+                        tx_hash,
+                        signer_id,
+                        e.kind(),
+                        e
+                    );
+                    format!("Error fetching transaction: {}", e.user_message())
+                }
+            };
+            if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                log::error!(
+                    "Failed to send Tx response chat_id={}: {}",
+                    msg.chat.id.0,
+                    e
+                );
+                return Err(e);
+            }
+        }
+        Command::Staking(args) => {
+            log::info!("Staking command chat_id={} args={}", msg.chat.id.0, args);
+            let parts: Vec<&str> = args.split_whitespace().collect();
+            if parts.len() != 2 {
+                if let Err(e) = bot
+                    .send_message(msg.chat.id, "Usage: /staking <account_id> <pool_id>")
+                    .await
+                {
+                    log::error!(
+                        "Failed to send Staking validation error chat_id={}: {}",
+                        msg.chat.id.0,
+                        e
+                    );
+                    return Err(e);
+                }
+                return Ok(());
+            }
+            let account_id = parts[0];
+            let pool = parts[1];
+
+            let message = match near_client.fetch_staking(pool, account_id).await {
+                Ok(staking) => format!(
+                    "Staking for {} with {}:\n\n{}",
+                    account_id,
+                    pool,
+                    utils::format_staking_details(&staking)
+                ),
+                Err(e) => {
+                    log::error!(
+                        "Staking command failed chat_id={} account={} pool={} kind={}: {}",
+                        msg.chat.id.0,
+                        account_id,
+                        pool,
+                        e.kind(),
+                        e
+                    );
+                    format!("Error fetching staking balances: {}", e.user_message())
+                }
+            };
+            if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                log::error!(
+                    "Failed to send Staking response chat_id={}: {}",
+                    msg.chat.id.0,
+                    e
+                );
+                return Err(e);
+            }
+        }
+        Command::Alert(ref args) | Command::Unalert(ref args) => {
+            let clear = matches!(cmd, Command::Unalert(_));
+            let parts: Vec<&str> = args.split_whitespace().collect();
+            log::info!(
+                "Alert command chat_id={} args={} clear={}",
+                msg.chat.id.0,
+                args,
+                clear
+            );
+            let message = match parts.split_first() {
+                None => "Please provide an account ID. Usage: /alert <account_id> [token_contract] [min=<amount>] [pct=<percent>] [below=<amount>] [above=<amount>] [dir=in|out|any]".to_string(),
+                Some((account_id, rest)) => {
+                    // An optional watch argument precedes the key=value rules.
+                    let (kind_arg, rule_args) = match rest.split_first() {
+                        Some((arg, rule_args)) if !arg.contains('=') => (Some(*arg), rule_args),
+                        _ => (None, rest),
+                    };
+                    let kind = WatchKind::from_arg(kind_arg);
+                    configure_alert(
+                        &monitored_accounts,
+                        &near_client,
+                        msg.chat.id,
+                        account_id,
+                        &kind,
+                        rule_args,
+                        clear,
+                    )
+                    .await
+                }
+            };
+            if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                log::error!(
+                    "Failed to send Alert response chat_id={}: {}",
+                    msg.chat.id.0,
+                    e
+                );
+                return Err(e);
+            }
+        }
+        Command::Notify(args) => {
+            let parts: Vec<&str> = args.split_whitespace().collect();
+            log::info!(
+                "Notify command chat_id={} args={}",
+                msg.chat.id.0,
+                args.split_whitespace()
+                    .take(2)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+            let message = match parts.split_first() {
+                None => "Please provide an account ID. Usage: /notify <account_id> [token_contract | pool:<pool_id> | keys] <telegram | slack <webhook_url> | discord <webhook_url> | matrix <room_id>>".to_string(),
+                Some((account_id, rest)) => {
+                    // An optional watch argument precedes the channel.
+                    let (kind_arg, channel_args) = match rest.split_first() {
+                        Some((arg, channel_args))
+                            if !matches!(*arg, "telegram" | "slack" | "discord" | "matrix") =>
+                        {
+                            (Some(*arg), channel_args)
+                        }
+                        _ => (None, rest),
+                    };
+                    let kind = WatchKind::from_arg(kind_arg);
+                    configure_channel(
+                        &monitored_accounts,
+                        &notifiers,
+                        msg.chat.id,
+                        account_id,
+                        &kind,
+                        channel_args,
+                    )
+                    .await
+                }
+            };
+            if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                log::error!(
+                    "Failed to send Notify response chat_id={}: {}",
+                    msg.chat.id.0,
+                    e
+                );
+                return Err(e);
+            }
+        }
+        Command::Floor(args) => {
+            let parts: Vec<&str> = args.split_whitespace().collect();
+            log::info!("Floor command chat_id={} args={}", msg.chat.id.0, args);
+            let message = match parts.split_first() {
+                None => "Please provide an account ID. Usage: /floor <account_id> [token_contract] <amount | off> [reminder, e.g. 1h | never]".to_string(),
+                Some((account_id, rest)) => {
+                    // An optional watch argument precedes the amount.
+                    let (kind_arg, floor_args) = match rest.split_first() {
+                        Some((arg, floor_args))
+                            if *arg != "off" && arg.parse::<NearAmount>().is_err() =>
+                        {
+                            (Some(*arg), floor_args)
+                        }
+                        _ => (None, rest),
+                    };
+                    let kind = WatchKind::from_arg(kind_arg);
+                    configure_floor(
+                        &monitored_accounts,
+                        &near_client,
+                        msg.chat.id,
+                        account_id,
+                        &kind,
+                        floor_args,
+                    )
+                    .await
+                }
+            };
+            if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                log::error!(
+                    "Failed to send Floor response chat_id={}: {}",
+                    msg.chat.id.0,
+                    e
+                );
+                return Err(e);
+            }
+        }
+        Command::Fiat(args) => {
+            let currency = args.trim().to_lowercase();
+            log::info!(
+                "Fiat command chat_id={} currency={}",
+                msg.chat.id.0,
+                currency
+            );
+            let message = if currency.is_empty() {
+                match chat_settings.lock().await.fiat(msg.chat.id) {
+                    Some(currency) => format!(
+                        "Balance alerts show values in {}. Use /fiat off to show NEAR only.",
+                        currency.to_uppercase()
+                    ),
+                    None => "Balance alerts show NEAR only. Usage: /fiat <currency | off>, e.g. /fiat usd".to_string(),
+                }
+            } else if currency == "off" {
+                chat_settings.lock().await.set_fiat(msg.chat.id, None);
+                "Balance alerts will show NEAR only.".to_string()
+            } else {
+                // Only accept currencies the price source can quote.
+                match prices.near_price(&currency).await {
+                    Ok(price) => {
+                        chat_settings
+                            .lock()
+                            .await
+                            .set_fiat(msg.chat.id, Some(currency.clone()));
+                        format!(
+                            "Balance alerts will show values in {} (1 NEAR = {}).",
+                            currency.to_uppercase(),
+                            utils::format_fiat(price, &currency)
+                        )
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Fiat command: price unavailable chat_id={} currency={} kind={}: {}",
                             msg.chat.id.0,
-                            send_err
+                            currency,
+                            e.kind(),
+                            e
                         );
-                        return Err(send_err);
+                        format!(
+                            "Could not get a NEAR price in {}: {}",
+                            currency.to_uppercase(),
+                            e.user_message()
+                        )
                     }
                 }
+            };
+            if let Err(e) = bot.send_message(msg.chat.id, message).await {
+                log::error!(
+                    "Failed to send Fiat response chat_id={}: {}",
+                    msg.chat.id.0,
+                    e
+                );
+                return Err(e);
             }
         }
     };
     Ok(())
 }
+
+/// Handles presses of the "next page" button below `/trxs` listings.
+///
+/// Buttons whose request was evicted from [`TxPages`] (or that predate a
+/// restart) get a hint to run `/trxs` again.
+async fn answer_callback(
+    bot: Bot,
+    query: CallbackQuery,
+    near_client: Arc<NearClient>,
+    tx_pages: Arc<Mutex<TxPages>>,
+) -> ResponseResult<()> {
+    bot.answer_callback_query(query.id.clone()).await?;
+    let Some(chat_id) = query.regular_message().map(|message| message.chat.id) else {
+        return Ok(());
+    };
+    let Some(id) = query
+        .data
+        .as_deref()
+        .and_then(|data| data.strip_prefix(TX_PAGE_CALLBACK_PREFIX))
+        .and_then(|id| id.parse::<u64>().ok())
+    else {
+        log::warn!(
+            "Unknown callback data chat_id={} data={:?}",
+            chat_id.0,
+            query.data
+        );
+        return Ok(());
+    };
+    let request = tx_pages.lock().await.get(id);
+    log::info!(
+        "Trxs next page chat_id={} page_id={} found={}",
+        chat_id.0,
+        id,
+        request.is_some()
+    );
+    match request {
+        Some(request) => send_transactions(&bot, chat_id, &near_client, &tx_pages, request).await,
+        None => {
+            if let Err(e) = bot
+                .send_message(chat_id, "This page has expired, please run /trxs again.")
+                .await
+            {
+                log::error!(
+                    "Failed to send Trxs expired page response chat_id={}: {}",
+                    chat_id.0,
+                    e
+                );
+                return Err(e);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Fetches one page of transactions and sends it to the chat, with a
+/// "next page" button if more transactions are available.
+///
+/// An empty `cursor` in the request starts from the newest transaction.
+async fn send_transactions(
+    bot: &Bot,
+    chat_id: ChatId,
+    near_client: &NearClient,
+    tx_pages: &Mutex<TxPages>,
+    request: TxPageRequest,
+) -> ResponseResult<()> {
+    let is_first_page = request.cursor.is_empty();
+    let cursor = Some(request.cursor.as_str()).filter(|cursor| !cursor.is_empty());
+    let result = near_client
+        .fetch_transactions(
+            &request.account_id,
+            &TransactionFilter::default(),
+            request.count,
+            cursor,
+        )
+        .await;
+    let page = match result {
+        Ok(page) => page,
+        Err(e) => {
+            log::error!(
+                "Trxs command failed chat_id={} account={} kind={}: {}",
+                chat_id.0,
+                request.account_id,
+                e.kind(),
+                e
+            );
+            if let Err(send_err) = bot
+                .send_message(
+                    chat_id,
+                    format!("Error fetching transactions: {}", e.user_message()),
+                )
+                .await
+            {
+                log::error!(
+                    "Failed to send Trxs error response chat_id={}: {}",
+                    chat_id.0,
+                    send_err
+                );
+                return Err(send_err);
+            }
+            return Ok(());
+        }
+    };
+
+    if page.transactions.is_empty() {
+        let message = if is_first_page {
+            format!("No transactions found for {}.", request.account_id)
+        } else {
+            format!("No more transactions found for {}.", request.account_id)
+        };
+        if let Err(e) = bot.send_message(chat_id, message).await {
+            log::error!(
+                "Failed to send Trxs empty response chat_id={}: {}",
+                chat_id.0,
+                e
+            );
+            return Err(e);
+        }
+        return Ok(());
+    }
+
+    let mut response = if is_first_page {
+        format!(
+            "Last {} transactions for {}:\n",
+            page.transactions.len(),
+            request.account_id
+        )
+    } else {
+        format!(
+            "Next {} transactions for {}:\n",
+            page.transactions.len(),
+            request.account_id
+        )
+    };
+    for tx in page.transactions {
+        let token = near_client.transaction_token(&tx).await;
+        response.push_str(&format!(
+            "\nTime: {}\nHash: {}...\nFrom: {}\nTo: {}\nAmount: {}\n",
+            utils::format_timestamp(tx.block_timestamp.clone()),
+            &tx.hash[..10],
+            tx.signer_id,
+            tx.receiver_id,
+            tx.actions_agg.deposit
+        ));
+        for action in &tx.actions {
+            response.push_str(&format!(
+                "• {}\n",
+                utils::describe_action(action, &tx.receiver_id, token.as_ref())
+            ));
+        }
+    }
+    let mut send = bot.send_message(chat_id, truncate_message(response));
+    if let Some(cursor) = page.cursor {
+        let id = tx_pages
+            .lock()
+            .await
+            .insert(TxPageRequest { cursor, ..request });
+        send = send.reply_markup(InlineKeyboardMarkup::new([[
+            InlineKeyboardButton::callback("Next page ▶", format!("{TX_PAGE_CALLBACK_PREFIX}{id}")),
+        ]]));
+    }
+    if let Err(e) = send.await {
+        log::error!(
+            "Failed to send Trxs success response chat_id={}: {}",
+            chat_id.0,
+            e
+        );
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Shortens a message to Telegram's limit of 4096 characters.
+fn truncate_message(message: String) -> String {
+    const MAX_MESSAGE_CHARS: usize = 4096;
+    if message.chars().count() <= MAX_MESSAGE_CHARS {
+        return message;
+    }
+    let mut short: String = message.chars().take(MAX_MESSAGE_CHARS - 2).collect();
+    short.push_str("\n…");
+    short
+}
+
+/// Compares a fresh staking pool delegation with the last known one, alerts
+/// the chat about accrued rewards, newly withdrawable unstakes and other
+/// changes, and persists the new state once the alert is delivered.
+///
+/// The first observation of a delegation is stored without an alert.
+async fn check_staking(
+    notifiers: &Notifiers,
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    account: &MonitoredAccount,
+    pool: &str,
+    current: &StakingBalance,
+) {
+    if account.last_staking.as_ref() == Some(current) {
+        return;
+    }
+    log::info!(
+        "Staking change detected account={} pool={} chat_id={} old={:?} new={:?}",
+        account.account_id,
+        pool,
+        account.chat_id,
+        account.last_staking,
+        current
+    );
+
+    if let Some(previous) = &account.last_staking {
+        let events = staking_events(previous, current);
+        if !events.is_empty() {
+            let message = format!(
+                "🥩 Staking Update for {} (pool {})!\n\n{}\n\n{}",
+                account.account_id,
+                pool,
+                events.join("\n"),
+                utils::format_staking_details(current)
+            );
+            // Keep the old state if the alert was lost, so it is sent again
+            // next cycle.
+            if !deliver_alert(notifiers, account, &message).await {
+                return;
+            }
+        }
+    }
+
+    let mut guard = monitored_accounts.lock().await;
+    guard.update_staking(
+        &account.account_id,
+        &account.kind,
+        account.chat_id,
+        *current,
+    );
+}
+
+/// Compares a fresh access key list with the last known one, alerts the chat
+/// about added and removed keys, and persists the new list once the alert is
+/// delivered.
+///
+/// Keys are compared by public key only: the allowance of function-call keys
+/// shrinks with every use and is not worth an alert. The first observation
+/// is stored without an alert.
+async fn check_keys(
+    notifiers: &Notifiers,
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    account: &MonitoredAccount,
+    current: &[AccessKey],
+) {
+    if account.last_keys.as_deref() == Some(current) {
+        return;
+    }
+
+    if let Some(previous) = &account.last_keys {
+        let added: Vec<&AccessKey> = current
+            .iter()
+            .filter(|k| !previous.iter().any(|p| p.public_key == k.public_key))
+            .collect();
+        let removed: Vec<&AccessKey> = previous
+            .iter()
+            .filter(|p| !current.iter().any(|k| k.public_key == p.public_key))
+            .collect();
+        let changed: Vec<(&AccessKey, &AccessKey)> = previous
+            .iter()
+            .filter_map(|p| {
+                current
+                    .iter()
+                    .find(|k| k.public_key == p.public_key)
+                    .filter(|k| permission_changed(&p.permission, &k.permission))
+                    .map(|k| (p, k))
+            })
+            .collect();
+
+        if !added.is_empty() || !removed.is_empty() || !changed.is_empty() {
+            log::info!(
+                "Access key change detected account={} chat_id={} added={} removed={} changed={}",
+                account.account_id,
+                account.chat_id,
+                added.len(),
+                removed.len(),
+                changed.len()
+            );
+
+            let mut message = format!("🔑 Access Keys Changed for {}!\n", account.account_id);
+            if added
+                .iter()
+                .any(|k| k.permission == AccessKeyPermission::FullAccess)
+            {
+                message.push_str("\n⚠️ A full-access key was added!\n");
+            }
+            if changed
+                .iter()
+                .any(|(_, k)| k.permission == AccessKeyPermission::FullAccess)
+            {
+                message.push_str("\n⚠️ An existing key was given full access!\n");
+            }
+            for key in &added {
+                message.push_str(&format!("\n+ {}", utils::format_access_key(key)));
+            }
+            for key in &removed {
+                message.push_str(&format!("\n- {}", utils::format_access_key(key)));
+            }
+            for (old, new) in &changed {
+                message.push_str(&format!(
+                    "\n~ {}\n  was {}",
+                    utils::format_access_key(new),
+                    utils::format_access_key(old)
+                ));
+            }
+            // Keep the old keys if the alert was lost, so it is sent again
+            // next cycle.
+            if !deliver_alert(notifiers, account, &message).await {
+                return;
+            }
+        }
+    }
+
+    let mut guard = monitored_accounts.lock().await;
+    guard.update_keys(&account.account_id, account.chat_id, current.to_vec());
+}
+
+/// Returns `true` if a key's permission changed between two observations.
+///
+/// A function-call key's allowance shrinks as it pays for gas, so only a
+/// larger (or newly unlimited) allowance counts: that means the key was
+/// deleted and added again.
+fn permission_changed(previous: &AccessKeyPermission, current: &AccessKeyPermission) -> bool {
+    match (previous, current) {
+        (
+            AccessKeyPermission::FunctionCall {
+                allowance: old_allowance,
+                receiver_id: old_receiver,
+                method_names: old_methods,
+            },
+            AccessKeyPermission::FunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+            },
+        ) => {
+            let allowance_raised = match (old_allowance, allowance) {
+                (Some(old), Some(new)) => new > old,
+                (Some(_), None) => true,
+                _ => false,
+            };
+            old_receiver != receiver_id || old_methods != method_names || allowance_raised
+        }
+        (previous, current) => previous != current,
+    }
+}
+
+/// Describes what changed between two observations of a delegation.
+fn staking_events(previous: &StakingBalance, current: &StakingBalance) -> Vec<String> {
+    let mut events = Vec::new();
+    if current.staked > previous.staked && current.unstaked == previous.unstaked {
+        events.push(format!(
+            "💰 Rewards accrued: +{}",
+            utils::format_near(current.staked - previous.staked)
+        ));
+    } else if current.staked != previous.staked || current.unstaked != previous.unstaked {
+        events.push(format!(
+            "Staked: {} → {}\nUnstaked: {} → {}",
+            utils::format_near(previous.staked),
+            utils::format_near(current.staked),
+            utils::format_near(previous.unstaked),
+            utils::format_near(current.unstaked)
+        ));
+    }
+    if current.is_withdrawable() && !previous.is_withdrawable() {
+        events.push(format!(
+            "✅ {} unstaked is now available to withdraw.",
+            utils::format_near(current.unstaked)
+        ));
+    }
+    events
+}
+
+/// Sends the "Balance Update" alert for a watched NEAR or token balance.
+///
+/// Native balances also show their fiat value if the chat has chosen a
+/// currency with `/fiat`. Returns the hashes of the listed transactions.
+async fn send_balance_alert(
+    notifiers: &Notifiers,
+    near_client: &NearClient,
+    chat_settings: &Mutex<ChatSettingsManager>,
+    prices: &PriceCache,
+    account: &MonitoredAccount,
+    current_balance: u128,
+) -> Vec<String> {
+    // Fiat values only apply to native NEAR balances.
+    let fiat = match account.kind {
+        WatchKind::Native => {
+            let fiat = chat_settings.lock().await.fiat(account.chat_id);
+            match fiat {
+                Some(currency) => prices
+                    .try_near_price(&currency)
+                    .await
+                    .map(|price| (currency, price)),
+                None => None,
+            }
+        }
+        _ => None,
+    };
+    let with_fiat = |amount: u128, formatted: String| match &fiat {
+        Some((currency, price)) => format!(
+            "{} ({})",
+            formatted,
+            utils::format_near_fiat(amount, *price, currency)
+        ),
+        None => formatted,
+    };
+    let old = match account.last_balance {
+        Some(balance) => with_fiat(
+            balance,
+            format_amount(near_client, &account.kind, balance).await,
+        ),
+        None => "Unknown".to_string(),
+    };
+    let new = with_fiat(
+        current_balance,
+        format_amount(near_client, &account.kind, current_balance).await,
+    );
+    let mut message = format!(
+        "🚨 Balance Update for {}{}!\n\nOld: {}\nNew: {}",
+        account.account_id, account.kind, old, new
+    );
+    if let Some(previous) = account.last_balance {
+        let delta = current_balance.abs_diff(previous);
+        let sign = if current_balance < previous { "-" } else { "+" };
+        message.push_str(&format!(
+            "\nChange: {}{}",
+            sign,
+            with_fiat(
+                delta,
+                format_amount(near_client, &account.kind, delta).await
+            )
+        ));
+        if let Some(percent) = alert::percent_change(previous, current_balance) {
+            message.push_str(&format!(" ({percent:+.2}%)"));
+        }
+    }
+    let page = fetch_causing_transactions(near_client, account).await;
+    if let Some(page) = &page {
+        let transactions = describe_causing_transactions(near_client, account, page).await;
+        message.push_str(&format!("\n\n{transactions}"));
+    }
+
+    deliver_alert(notifiers, account, &truncate_message(message)).await;
+    page.map(|page| page.transactions.into_iter().map(|tx| tx.hash).collect())
+        .unwrap_or_default()
+}
+
+/// Sends an alert through the channel of the watch.
+///
+/// If another channel than Telegram fails, the alert goes to the chat
+/// instead, together with the reason, so that it is not lost. Returns
+/// `false` if the alert could not be delivered at all.
+async fn deliver_alert(notifiers: &Notifiers, account: &MonitoredAccount, message: &str) -> bool {
+    let result = match notifiers.for_channel(&account.channel, account.chat_id) {
+        Ok(notifier) => send_bounded(notifier.as_ref(), message)
+            .await
+            .map_err(|e| (notifier.name(), e)),
+        Err(e) => Err(("unavailable", e)),
+    };
+    let Err((channel, e)) = result else {
+        return true;
+    };
+    log::error!(
+        "Failed to send alert account={}{} chat_id={} channel={} kind={}: {}",
+        account.account_id,
+        account.kind,
+        account.chat_id,
+        channel,
+        e.kind(),
+        e
+    );
+    if account.channel.is_telegram() {
+        return false;
+    }
+    let fallback = format!(
+        "Could not deliver this alert to {}: {}\n\n{}",
+        account.channel, e, message
+    );
+    let telegram = notifiers.for_channel(&AlertChannel::Telegram, account.chat_id);
+    if let Err(e) = match telegram {
+        Ok(notifier) => send_bounded(notifier.as_ref(), &truncate_message(fallback)).await,
+        Err(e) => Err(e),
+    } {
+        log::error!(
+            "Failed to send fallback alert chat_id={}: {}",
+            account.chat_id,
+            e
+        );
+        return false;
+    }
+    true
+}
+
+/// Sends `message`, giving up after [`ALERT_SEND_TIMEOUT`] so that a stuck
+/// channel cannot stall the monitoring loop.
+async fn send_bounded(notifier: &dyn Notifier, message: &str) -> Result<(), Error> {
+    tokio::time::timeout(ALERT_SEND_TIMEOUT, notifier.send(message))
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::Transport(format!(
+                "timed out after {}s",
+                ALERT_SEND_TIMEOUT.as_secs()
+            )))
+        })
+}
+
+/// Fetches the transactions of an account since its last recorded balance,
+/// which explain a balance change, or returns `None` if there are none or
+/// they cannot be fetched.
+///
+/// At most [`MAX_ALERT_TRANSACTIONS`] are returned, newest first.
+async fn fetch_causing_transactions(
+    near_client: &NearClient,
+    account: &MonitoredAccount,
+) -> Option<TransactionPage> {
+    let since = chrono::DateTime::from_timestamp(account.last_balance_at?, 0)?;
+    let filter = TransactionFilter {
+        since: Some(since),
+        ..TransactionFilter::default()
+    };
+    let mut page = near_client
+        .fetch_transactions(&account.account_id, &filter, MAX_ALERT_TRANSACTIONS, None)
+        .await
+        .inspect_err(|e| {
+            log::warn!(
+                "Alert transactions unavailable account={} kind={}: {}",
+                account.account_id,
+                e.kind(),
+                e
+            )
+        })
+        .ok()?;
+    // Transactions shortly before the last update may already have been
+    // listed by the previous alert.
+    page.transactions
+        .retain(|tx| !account.reported_transactions.contains(&tx.hash));
+    if page.transactions.is_empty() {
+        return None;
+    }
+    Some(page)
+}
+
+/// Lists the transactions of [`fetch_causing_transactions`] for an alert.
+async fn describe_causing_transactions(
+    near_client: &NearClient,
+    account: &MonitoredAccount,
+    page: &TransactionPage,
+) -> String {
+    let mut lines = vec!["Transactions since the last update:".to_string()];
+    for tx in &page.transactions {
+        // Token transfers carry their amount in the call arguments.
+        let amount = match near_client.transaction_token(tx).await {
+            Some(token) => tx
+                .actions
+                .iter()
+                .filter(|action| action.is_token_transfer())
+                .map(|action| utils::describe_action(action, &tx.receiver_id, Some(&token)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => tx.actions_agg.deposit.to_string(),
+        };
+        lines.push(format!(
+            "- {} {} → {}: {}\n  {}",
+            utils::format_timestamp(tx.block_timestamp.clone()),
+            tx.signer_id,
+            tx.receiver_id,
+            amount,
+            tx.hash
+        ));
+    }
+    if page.cursor.is_some() {
+        lines.push(format!("… see /trxs {} for more", account.account_id));
+    }
+    lines.join("\n")
+}
+
+/// Shows, changes or (with `clear`) resets the alert rules of a watch and
+/// returns the reply for the chat.
+///
+/// `rule_args` are `key=value` pairs: `min` (minimum change), `pct`
+/// (minimum change in percent), `below`, `above` (floor and ceiling) and
+/// `dir` (`in`, `out` or `any`). The value `off` removes a rule.
+async fn configure_alert(
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    near_client: &NearClient,
+    chat_id: ChatId,
+    account_id: &str,
+    kind: &WatchKind,
+    rule_args: &[&str],
+    clear: bool,
+) -> String {
+    let mut account = match find_balance_watch(monitored_accounts, chat_id, account_id, kind).await
+    {
+        Ok(account) => account,
+        Err(reply) => return reply,
+    };
+
+    if clear {
+        account.rules = AlertRules::default();
+    } else if !rule_args.is_empty() {
+        let decimals = match watch_decimals(near_client, kind).await {
+            Ok(decimals) => decimals,
+            Err(reply) => return reply,
+        };
+        for arg in rule_args {
+            if let Err(e) = apply_rule_arg(&mut account.rules, arg, kind, decimals) {
+                return format!(
+                    "{e}\n\nUsage: /alert <account_id> [token_contract] [min=<amount>] [pct=<percent>] [below=<amount>] [above=<amount>] [dir=in|out|any]"
+                );
+            }
+        }
+    }
+    if clear || !rule_args.is_empty() {
+        monitored_accounts.lock().await.update_rules(
+            account_id,
+            kind,
+            chat_id,
+            account.rules.clone(),
+        );
+    }
+    format!(
+        "Alert rules for {}{}: {}",
+        account_id,
+        kind,
+        describe_rules(near_client, &account).await
+    )
+}
+
+/// Applies one `key=value` argument of `/alert` to `rules`.
+///
+/// Native amounts accept units (e.g. "1500 mNEAR"); token amounts are
+/// decimal numbers scaled by `decimals`.
+fn apply_rule_arg(
+    rules: &mut AlertRules,
+    arg: &str,
+    kind: &WatchKind,
+    decimals: u32,
+) -> Result<(), String> {
+    let Some((key, value)) = arg.split_once('=') else {
+        return Err(format!("Expected key=value, got '{arg}'."));
+    };
+    let amount = || -> Result<Option<u128>, String> {
+        if value == "off" {
+            return Ok(None);
+        }
+        parse_watch_amount(value, kind, decimals)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid amount '{value}' for {key}."))
+    };
+    match key {
+        "min" => rules.min_delta = amount()?,
+        "below" => rules.below = amount()?,
+        "above" => rules.above = amount()?,
+        "pct" => {
+            rules.min_percent = match value {
+                "off" => None,
+                _ => Some(
+                    value
+                        .trim_end_matches('%')
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|percent| percent.is_finite() && *percent >= 0.0)
+                        .ok_or_else(|| format!("Invalid percentage '{value}'."))?,
+                ),
+            }
+        }
+        "dir" => {
+            rules.direction = match value {
+                "in" => Some(Direction::In),
+                "out" => Some(Direction::Out),
+                "any" | "off" => None,
+                _ => return Err(format!("Invalid direction '{value}', use in, out or any.")),
+            }
+        }
+        _ => return Err(format!("Unknown rule '{key}'.")),
+    }
+    Ok(())
+}
+
+/// Returns the caller's native or token watch of `account_id`, or the reply
+/// explaining why it cannot have alert settings.
+async fn find_balance_watch(
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    chat_id: ChatId,
+    account_id: &str,
+    kind: &WatchKind,
+) -> Result<MonitoredAccount, String> {
+    let account = monitored_accounts
+        .lock()
+        .await
+        .get_accounts_for_chat(chat_id)
+        .into_iter()
+        .find(|a| a.account_id == account_id && &a.kind == kind)
+        .cloned();
+    let Some(account) = account else {
+        return Err(format!(
+            "{}{} is not in your monitoring list. Add it with /add first.",
+            account_id, kind
+        ));
+    };
+    if matches!(kind, WatchKind::Staking { .. } | WatchKind::Keys) {
+        return Err("Alert settings apply to NEAR and token balances only.".to_string());
+    }
+    Ok(account)
+}
+
+/// Returns the decimals of the watched balance: 24 for NEAR, or the token's
+/// decimals from its metadata. Errors are returned as the reply to send.
+async fn watch_decimals(near_client: &NearClient, kind: &WatchKind) -> Result<u32, String> {
+    match kind {
+        WatchKind::Token { contract } => match near_client.ft_metadata(contract).await {
+            Ok(metadata) => Ok(u32::from(metadata.decimals)),
+            Err(e) => Err(format!(
+                "Could not read token metadata from {}: {}",
+                contract,
+                e.user_message()
+            )),
+        },
+        _ => Ok(NEAR_DECIMALS),
+    }
+}
+
+/// Parses an amount of the watched balance.
+///
+/// Native amounts accept units (e.g. "1500 mNEAR"); token amounts are
+/// decimal numbers scaled by `decimals`.
+fn parse_watch_amount(value: &str, kind: &WatchKind, decimals: u32) -> Option<u128> {
+    match kind {
+        WatchKind::Native => value.parse::<NearAmount>().ok().map(|a| a.as_yocto()),
+        _ => amount::parse_decimal(value, decimals),
+    }
+}
+
+/// Shows, sets or (with `off`) removes the low-balance guard of a watch and
+/// returns the reply for the chat.
+///
+/// `floor_args` is empty (show), `off`, or an amount optionally followed by
+/// the reminder interval (e.g. `1h`, or `never`; default
+/// [`DEFAULT_FLOOR_REMINDER_SECS`]).
+async fn configure_floor(
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    near_client: &NearClient,
+    chat_id: ChatId,
+    account_id: &str,
+    kind: &WatchKind,
+    floor_args: &[&str],
+) -> String {
+    let mut account = match find_balance_watch(monitored_accounts, chat_id, account_id, kind).await
+    {
+        Ok(account) => account,
+        Err(reply) => return reply,
+    };
+    let usage =
+        "Usage: /floor <account_id> [token_contract] <amount | off> [reminder, e.g. 1h | never]";
+    match floor_args {
+        [] => {}
+        ["off"] => {
+            account.floor = None;
+            monitored_accounts
+                .lock()
+                .await
+                .update_floor(account_id, kind, chat_id, None);
+            return format!("Removed the low-balance floor of {}{}.", account_id, kind);
+        }
+        [amount] | [amount, _] => {
+            let decimals = match watch_decimals(near_client, kind).await {
+                Ok(decimals) => decimals,
+                Err(reply) => return reply,
+            };
+            let Some(floor) = parse_watch_amount(amount, kind, decimals) else {
+                return format!("Invalid amount '{amount}'.\n\n{usage}");
+            };
+            let reminder_secs = match floor_args.get(1) {
+                None => DEFAULT_FLOOR_REMINDER_SECS,
+                Some(&"never") => 0,
+                Some(reminder) => match utils::parse_step(reminder) {
+                    Ok(step) => step.num_seconds().unsigned_abs(),
+                    Err(e) => return format!("{e}\n\n{usage}"),
+                },
+            };
+            account.floor = Some(FloorGuard::new(floor, reminder_secs));
+            monitored_accounts.lock().await.update_floor(
+                account_id,
+                kind,
+                chat_id,
+                account.floor.clone(),
+            );
+        }
+        _ => return usage.to_string(),
+    }
+    match account.floor {
+        Some(_) => format!(
+            "Low-balance floor for {}{}: {}",
+            account_id,
+            kind,
+            describe_floor(near_client, &account).await
+        ),
+        None => format!(
+            "{}{} has no low-balance floor.\n\n{}",
+            account_id, kind, usage
+        ),
+    }
+}
+
+/// Describes the low-balance guard of a watch, e.g.
+/// "5.0000 NEAR, reminders every 1h, currently below".
+async fn describe_floor(near_client: &NearClient, account: &MonitoredAccount) -> String {
+    let Some(floor) = &account.floor else {
+        return "none".to_string();
+    };
+    let mut description = format_amount(near_client, &account.kind, floor.floor).await;
+    if floor.reminder_secs > 0 {
+        description.push_str(&format!(
+            ", reminders every {}",
+            utils::format_step(floor.reminder_secs)
+        ));
+    }
+    if floor.is_below() {
+        description.push_str(", currently below");
+    }
+    description
+}
+
+/// Checks the low-balance guard of a watch against the current balance,
+/// sends the crossing, reminder or recovery message and, once it was
+/// delivered, persists the new guard state.
+async fn check_floor(
+    notifiers: &Notifiers,
+    near_client: &NearClient,
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    account: &MonitoredAccount,
+    checked: &FloorGuard,
+    current_balance: u128,
+) {
+    let mut floor = checked.clone();
+    let Some(event) = floor.check(current_balance, chrono::Utc::now().timestamp()) else {
+        return;
+    };
+    log::info!(
+        "Floor event account={}{} chat_id={} event={:?} balance={} floor={}",
+        account.account_id,
+        account.kind,
+        account.chat_id,
+        event,
+        current_balance,
+        floor.floor
+    );
+    let balance = format_amount(near_client, &account.kind, current_balance).await;
+    let threshold = format_amount(near_client, &account.kind, floor.floor).await;
+    let message = match event {
+        FloorEvent::Crossed => format!(
+            "⚠️ Low balance: {}{} is at {}, below the floor of {}.",
+            account.account_id, account.kind, balance, threshold
+        ),
+        FloorEvent::Reminder { since } => format!(
+            "⏰ Still low: {}{} is at {}, below the floor of {} since {}.",
+            account.account_id,
+            account.kind,
+            balance,
+            threshold,
+            utils::format_timestamp((since * 1_000_000_000).to_string())
+        ),
+        FloorEvent::Recovered => format!(
+            "✅ Recovered: {}{} is back at {}, above the floor of {}.",
+            account.account_id, account.kind, balance, threshold
+        ),
+    };
+    // Keep the old state if the alert was lost, so it is sent again next cycle.
+    if !deliver_alert(notifiers, account, &message).await {
+        return;
+    }
+    monitored_accounts.lock().await.update_floor_state(
+        &account.account_id,
+        &account.kind,
+        account.chat_id,
+        checked,
+        &floor,
+    );
+}
+
+/// Shows or changes where the alerts of a watch are delivered and returns
+/// the reply for the chat.
+///
+/// A test message is sent through a new channel before it is saved, so that
+/// a mistyped webhook URL or room ID does not silently swallow alerts.
+async fn configure_channel(
+    monitored_accounts: &Mutex<AccountPersistenceManager>,
+    notifiers: &Notifiers,
+    chat_id: ChatId,
+    account_id: &str,
+    kind: &WatchKind,
+    channel_args: &[&str],
+) -> String {
+    let account = monitored_accounts
+        .lock()
+        .await
+        .get_accounts_for_chat(chat_id)
+        .into_iter()
+        .find(|a| a.account_id == account_id && &a.kind == kind)
+        .cloned();
+    let Some(account) = account else {
+        return format!(
+            "{}{} is not in your monitoring list. Add it with /add first.",
+            account_id, kind
+        );
+    };
+    if channel_args.is_empty() {
+        return format!(
+            "Alerts of {}{} are sent to {}.",
+            account_id, kind, account.channel
+        );
+    }
+    let channel = match AlertChannel::parse(channel_args) {
+        Ok(channel) => channel,
+        Err(reply) => {
+            return format!(
+                "{reply}\n\nUsage: /notify <account_id> [token_contract | pool:<pool_id> | keys] <telegram | slack <webhook_url> | discord <webhook_url> | matrix <room_id>>"
+            );
+        }
+    };
+    if let AlertChannel::Matrix { room_id } = &channel {
+        if !notifiers.has_matrix() {
+            return "Matrix is not configured for this bot.".to_string();
+        }
+        if !notifiers.allows_matrix_room(room_id) {
+            return format!(
+                "The bot is not allowed to post to {room_id}. Ask its operator to add the room to --matrix-rooms."
+            );
+        }
+    }
+    if !channel.is_telegram() {
+        let test = format!("🔔 Alerts of {}{} will be sent here.", account_id, kind);
+        let sent = match notifiers.for_channel(&channel, chat_id) {
+            Ok(notifier) => send_bounded(notifier.as_ref(), &test).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = sent {
+            log::warn!(
+                "Alert channel test failed account={}{} chat_id={} kind={}: {}",
+                account_id,
+                kind,
+                chat_id,
+                e.kind(),
+                e
+            );
+            return format!(
+                "Could not send a test message to {channel}: {e}\nAlerts are still sent to {}.",
+                account.channel
+            );
+        }
+    }
+    let reply = format!(
+        "Alerts of {}{} will now be sent to {}.",
+        account_id, kind, channel
+    );
+    monitored_accounts
+        .lock()
+        .await
+        .update_channel(account_id, kind, chat_id, channel);
+    reply
+}
+
+/// Describes the alert rules of a watch, formatting amounts like the
+/// watched balance.
+async fn describe_rules(near_client: &NearClient, account: &MonitoredAccount) -> String {
+    let metadata = match &account.kind {
+        WatchKind::Token { contract } => near_client.ft_metadata(contract).await.ok(),
+        _ => None,
+    };
+    account.rules.describe(|amount| match &metadata {
+        Some(metadata) => utils::format_token(amount, metadata.decimals, &metadata.symbol),
+        None => utils::format_near(amount),
+    })
+}
+
+/// Formats a watched amount: NEAR for native balances, or the token's
+/// symbol and decimals for fungible tokens.
+///
+/// Falls back to the raw amount if the token metadata cannot be fetched.
+async fn format_amount(near_client: &NearClient, kind: &WatchKind, amount: u128) -> String {
+    match kind {
+        WatchKind::Native | WatchKind::Staking { .. } | WatchKind::Keys => {
+            utils::format_near(amount)
+        }
+        WatchKind::Token { contract } => match near_client.ft_metadata(contract).await {
+            Ok(metadata) => utils::format_token(amount, metadata.decimals, &metadata.symbol),
+            Err(e) => {
+                log::warn!(
+                    "Failed to fetch token metadata token={} kind={}: {}",
+                    contract,
+                    e.kind(),
+                    e
+                );
+                format!("{} (raw units of {})", amount, contract)
+            }
+        },
+    }
+}
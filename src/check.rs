@@ -0,0 +1,169 @@
+//! Nagios-style balance probe for the `check` command.
+//!
+//! The probe compares an account's balance with warning and critical floors
+//! and reports the result as one status line with performance data, e.g.
+//!
+//! ```text
+//! NEAR WARNING - relayer.near balance 4.2000 NEAR (below 5.0000 NEAR) | balance=4.2;5:;1:;0; available=4.19818;;;0;
+//! ```
+//!
+//! and the exit code monitoring systems expect: 0 (OK), 1 (WARNING),
+//! 2 (CRITICAL) or 3 (UNKNOWN).
+
+use crate::error::Error;
+use crate::near::AccountSnapshot;
+use crate::output::near_decimal;
+use crate::utils;
+
+/// Result of a check, in increasing severity except for `Unknown`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The balance is at or above both floors.
+    Ok,
+    /// The balance is below the warning floor.
+    Warning,
+    /// The balance is below the critical floor, or the account does not exist.
+    Critical,
+    /// The balance could not be determined.
+    Unknown,
+}
+
+impl CheckStatus {
+    /// Returns the Nagios plugin exit code.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            CheckStatus::Ok => 0,
+            CheckStatus::Warning => 1,
+            CheckStatus::Critical => 2,
+            CheckStatus::Unknown => 3,
+        }
+    }
+
+    /// Returns the status label used in the status line, e.g. "WARNING".
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Warning => "WARNING",
+            CheckStatus::Critical => "CRITICAL",
+            CheckStatus::Unknown => "UNKNOWN",
+        }
+    }
+}
+
+/// Warning and critical floors in yoctoNEAR.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Thresholds {
+    /// Balance below which the check warns.
+    pub warn_below: Option<u128>,
+    /// Balance below which the check is critical.
+    pub crit_below: Option<u128>,
+}
+
+impl Thresholds {
+    /// Checks that the critical floor is not above the warning floor.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Config` error for inverted floors.
+    pub fn validate(&self) -> Result<(), Error> {
+        match (self.warn_below, self.crit_below) {
+            (Some(warn), Some(crit)) if crit > warn => Err(Error::Config(format!(
+                "--crit-below {} is above --warn-below {}",
+                utils::format_near(crit),
+                utils::format_near(warn)
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the status for `balance`.
+    pub fn evaluate(&self, balance: u128) -> CheckStatus {
+        if self.crit_below.is_some_and(|crit| balance < crit) {
+            CheckStatus::Critical
+        } else if self.warn_below.is_some_and(|warn| balance < warn) {
+            CheckStatus::Warning
+        } else {
+            CheckStatus::Ok
+        }
+    }
+}
+
+/// Outcome of the `check` command.
+pub struct CheckResult {
+    /// Overall status.
+    pub status: CheckStatus,
+    /// Human-readable status line, including performance data.
+    pub line: String,
+    /// Balance in yoctoNEAR, if it could be fetched.
+    pub balance: Option<u128>,
+    /// Why the balance could not be fetched.
+    pub error: Option<String>,
+}
+
+/// Evaluates a fetched account against the floors.
+pub fn check_account(
+    account_id: &str,
+    account: &AccountSnapshot,
+    thresholds: &Thresholds,
+) -> CheckResult {
+    let status = thresholds.evaluate(account.amount);
+    let floor = match status {
+        CheckStatus::Critical => thresholds.crit_below,
+        CheckStatus::Warning => thresholds.warn_below,
+        CheckStatus::Ok | CheckStatus::Unknown => None,
+    };
+    let reason = floor
+        .map(|floor| format!(" (below {})", utils::format_near(floor)))
+        .unwrap_or_default();
+    // A floor is the range `floor:`, which alerts below it; a bare number
+    // would alert above it.
+    let threshold = |floor: Option<u128>| {
+        floor
+            .map(|floor| format!("{}:", near_decimal(floor)))
+            .unwrap_or_default()
+    };
+    // Perfdata: 'label'=value[UOM];[warn];[crit];[min];[max], in NEAR.
+    let perfdata = format!(
+        "balance={};{};{};0; available={};;;0;",
+        near_decimal(account.amount),
+        threshold(thresholds.warn_below),
+        threshold(thresholds.crit_below),
+        near_decimal(account.available())
+    );
+    CheckResult {
+        status,
+        line: format!(
+            "NEAR {} - {} balance {}{} | {}",
+            status.label(),
+            account_id,
+            utils::format_near(account.amount),
+            reason,
+            perfdata
+        ),
+        balance: Some(account.amount),
+        error: None,
+    }
+}
+
+/// Reports a failed balance lookup.
+///
+/// A missing account is `Critical`, since a probed account is expected to
+/// exist; any other failure means the balance is `Unknown`.
+pub fn check_error(account_id: &str, error: &Error) -> CheckResult {
+    let status = if error.is_account_not_found() {
+        CheckStatus::Critical
+    } else {
+        CheckStatus::Unknown
+    };
+    let message = if error.is_account_not_found() {
+        "account does not exist".to_string()
+    } else {
+        error.to_string()
+    };
+    CheckResult {
+        status,
+        line: format!("NEAR {} - {}: {}", status.label(), account_id, message),
+        balance: None,
+        error: Some(message),
+    }
+}
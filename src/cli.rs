@@ -3,17 +3,138 @@
 //! This module defines the CLI structure using `clap` derive macros.
 //! All CLI commands are defined here and parsed automatically by clap.
 
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::amount::NearAmount;
+use crate::near::Direction;
+use crate::network::Network;
+use crate::price::COINGECKO_API_URL;
+use crate::utils;
 
 /// Main CLI structure for the NEAR Balance Monitor application.
 #[derive(Parser)]
 #[command(name = "near-balance", about = "NEAR Protocol balance detector")]
 pub struct Cli {
+    /// Network selection shared by all commands
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    /// Retry behaviour for RPC and indexer requests
+    #[command(flatten)]
+    pub retry: RetryArgs,
+    /// Price source for fiat values
+    #[command(flatten)]
+    pub price: PriceArgs,
+    /// Output format of command results
+    #[arg(long, global = true, value_enum, env = "NEAR_OUTPUT", default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
     /// The subcommand to execute
     #[command(subcommand)]
     pub command: Commands,
 }
 
+impl Cli {
+    /// Returns `true` if `args` invoke the `check` command, even if they are
+    /// otherwise invalid, so that usage errors can be reported with the
+    /// plugin's UNKNOWN exit code.
+    pub fn is_check_invocation<I, T>(args: I) -> bool
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        Cli::command()
+            .ignore_errors(true)
+            .try_get_matches_from(args)
+            .is_ok_and(|matches| matches.subcommand_name() == Some("check"))
+    }
+}
+
+/// Global network selection options.
+///
+/// Every option can also be provided through the environment, which is
+/// convenient for the systemd service running the bot.
+#[derive(Args)]
+pub struct NetworkArgs {
+    /// NEAR network to connect to
+    #[arg(long, global = true, value_enum, env = "NEAR_NETWORK", default_value_t = Network::Mainnet)]
+    pub network: Network,
+    /// Override the RPC endpoint URL (required for the custom network).
+    /// Repeat or comma-separate to enable failover between endpoints.
+    #[arg(long, global = true, env = "NEAR_RPC_URL", value_delimiter = ',')]
+    pub rpc_url: Vec<String>,
+    /// Override the archival RPC endpoint URL used for historical queries.
+    /// Repeat or comma-separate to enable failover between endpoints.
+    #[arg(
+        long,
+        global = true,
+        env = "NEAR_ARCHIVAL_RPC_URL",
+        value_delimiter = ','
+    )]
+    pub archival_rpc_url: Vec<String>,
+    /// Override the NearBlocks API base URL
+    #[arg(long, global = true, env = "NEAR_INDEXER_URL")]
+    pub indexer_url: Option<String>,
+    /// API key for the RPC provider, sent only to --rpc-url and
+    /// --archival-rpc-url endpoints
+    #[arg(long, global = true, env = "NEAR_RPC_API_KEY", hide_env_values = true)]
+    pub rpc_api_key: Option<String>,
+    /// API key for the NearBlocks indexer
+    #[arg(
+        long,
+        global = true,
+        env = "NEAR_INDEXER_API_KEY",
+        hide_env_values = true
+    )]
+    pub indexer_api_key: Option<String>,
+}
+
+/// Global retry options for RPC and NearBlocks requests.
+///
+/// The bot reads the same settings, so they can be tuned for the service
+/// through the environment.
+#[derive(Args)]
+pub struct RetryArgs {
+    /// Maximum attempts per request, including the first (1 disables retries)
+    #[arg(long, global = true, env = "NEAR_RETRY_ATTEMPTS", default_value_t = 3)]
+    pub retry_attempts: u32,
+    /// Delay before the first retry in milliseconds; doubles on each retry
+    #[arg(long, global = true, env = "NEAR_RETRY_BASE_MS", default_value_t = 250)]
+    pub retry_base_ms: u64,
+    /// Maximum delay between retries in milliseconds
+    #[arg(long, global = true, env = "NEAR_RETRY_MAX_MS", default_value_t = 5000)]
+    pub retry_max_ms: u64,
+    /// Random jitter applied to retry delays, as a fraction between 0 and 1
+    #[arg(long, global = true, env = "NEAR_RETRY_JITTER", default_value_t = 0.5)]
+    pub retry_jitter: f64,
+}
+
+/// Global price source options for fiat values.
+///
+/// Prices come from a CoinGecko-compatible API unless `--price-file` points
+/// to a JSON file such as `{"usd": 3.45, "eur": 3.1}`.
+#[derive(Args)]
+pub struct PriceArgs {
+    /// Read NEAR prices from a JSON file mapping currency codes to prices
+    #[arg(long, global = true, env = "NEAR_PRICE_FILE")]
+    pub price_file: Option<PathBuf>,
+    /// CoinGecko-compatible price API base URL
+    #[arg(long, global = true, env = "NEAR_PRICE_URL", default_value = COINGECKO_API_URL)]
+    pub price_url: String,
+    /// API key for the price API
+    #[arg(
+        long,
+        global = true,
+        env = "NEAR_PRICE_API_KEY",
+        hide_env_values = true
+    )]
+    pub price_api_key: Option<String>,
+    /// Seconds a fetched price is reused before it is refreshed
+    #[arg(long, global = true, env = "NEAR_PRICE_TTL", default_value_t = 300)]
+    pub price_ttl: u64,
+}
+
 /// Available CLI commands.
 ///
 /// Each variant represents a different mode of operation:
@@ -21,26 +142,238 @@ pub struct Cli {
 /// - `Monitor`: Continuous balance monitoring
 /// - `Bot`: Telegram bot mode
 /// - `Txs`: Transaction history lookup
+/// - `Tx`: Transaction status and receipt drill-down
+/// - `Staking`: Staking pool delegation lookup
+/// - `Keys`: Access key inventory
+/// - `History`: Balance history over a date range
 #[derive(Subcommand)]
 pub enum Commands {
     /// Query and display current balance
     Balance {
         /// NEAR account ID (e.g., "example.near")
         account_id: String,
+        /// NEP-141 token contract to query instead of native NEAR
+        /// (e.g., "usdt.tether-token.near")
+        #[arg(long)]
+        token: Option<String>,
+        /// Read the balance at a past block, given by height or hash
+        #[arg(long, conflicts_with_all = ["at", "token"])]
+        at_block: Option<String>,
+        /// Read the balance at a past time: RFC 3339 (e.g., "2026-09-30T23:59:59Z")
+        /// or a date (end of that day, UTC)
+        #[arg(long, conflicts_with = "token", value_parser = utils::parse_datetime)]
+        at: Option<DateTime<Utc>>,
+        /// Also show the value in this fiat currency at the current price
+        /// (e.g., "usd", "eur")
+        #[arg(long, conflicts_with_all = ["token", "at_block", "at"])]
+        fiat: Option<String>,
     },
-    /// Monitor balance for changes over time
+    /// Monitor balances for changes over time
     Monitor {
-        /// NEAR account ID (e.g., "example.near")
-        account_id: String,
+        /// NEAR account IDs (e.g., "example.near")
+        #[arg(required_unless_present = "accounts_file")]
+        account_ids: Vec<String>,
+        /// Read more account IDs from a file, one per line ('#' starts a comment)
+        #[arg(long)]
+        accounts_file: Option<PathBuf>,
         /// Polling interval in seconds (default: 10s)
         #[arg(long, default_value_t = 10)]
         interval: u64,
+        /// How changes are shown
+        #[arg(long, value_enum, default_value_t = MonitorView::Lines)]
+        view: MonitorView,
+        /// Maximum concurrent balance requests per poll
+        #[arg(long, default_value_t = 8)]
+        concurrency: usize,
+        /// Also show the value in this fiat currency (e.g., "usd", "eur")
+        #[arg(long)]
+        fiat: Option<String>,
+        /// Only report changes of at least this amount since the last
+        /// reported balance (e.g., "0.5" or "500 mNEAR")
+        #[arg(long)]
+        min_delta: Option<NearAmount>,
+        /// Only report changes of at least this percentage of the last
+        /// reported balance
+        #[arg(long)]
+        min_percent: Option<f64>,
+        /// Only report changes leaving the balance below this amount
+        #[arg(long)]
+        below: Option<NearAmount>,
+        /// Only report changes leaving the balance above this amount
+        #[arg(long)]
+        above: Option<NearAmount>,
+        /// Only report incoming (increase) or outgoing (decrease) changes
+        #[arg(long, value_enum)]
+        direction: Option<Direction>,
+        /// Webhooks notified of reported changes
+        #[command(flatten)]
+        webhook: WebhookArgs,
     },
     /// Start Telegram bot for remote monitoring
-    Bot,
+    Bot {
+        /// Maximum concurrent balance requests per monitoring cycle
+        #[arg(long, env = "NEAR_CONCURRENCY", default_value_t = 16)]
+        concurrency: usize,
+        /// Webhooks notified of balance changes
+        #[command(flatten)]
+        webhook: WebhookArgs,
+        /// Matrix homeserver for alerts sent to Matrix rooms
+        /// (e.g., "https://matrix.org")
+        #[arg(
+            long,
+            env = "MATRIX_HOMESERVER",
+            requires_all = ["matrix_token", "matrix_rooms"]
+        )]
+        matrix_homeserver: Option<String>,
+        /// Access token of the Matrix user posting alerts
+        #[arg(
+            long,
+            env = "MATRIX_ACCESS_TOKEN",
+            hide_env_values = true,
+            requires = "matrix_homeserver"
+        )]
+        matrix_token: Option<String>,
+        /// Matrix room IDs that /notify may send alerts to; repeat or
+        /// comma-separate for several
+        #[arg(
+            long,
+            env = "MATRIX_ROOMS",
+            value_delimiter = ',',
+            requires = "matrix_homeserver"
+        )]
+        matrix_rooms: Vec<String>,
+    },
     /// Fetch and display recent transactions
     Txs {
         /// NEAR account ID (e.g., "example.near")
         account_id: String,
+        /// Maximum number of transactions to show
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        /// Only show transactions at or after this time: RFC 3339 or a date
+        /// (start of that day, UTC)
+        #[arg(long, value_parser = utils::parse_start_datetime)]
+        since: Option<DateTime<Utc>>,
+        /// Only show transactions at or before this time: RFC 3339 or a date
+        /// (end of that day, UTC)
+        #[arg(long, value_parser = utils::parse_datetime)]
+        until: Option<DateTime<Utc>>,
+        /// Only show incoming or outgoing transactions
+        #[arg(long, value_enum)]
+        direction: Option<Direction>,
+        /// Only show transactions sent by or to this account
+        #[arg(long)]
+        counterparty: Option<String>,
+        /// Only show transactions depositing at least this amount
+        /// (e.g., "1.5", "1.5 NEAR" or "1500 mNEAR")
+        #[arg(long)]
+        min_amount: Option<NearAmount>,
+        /// Continue from the cursor printed at the end of a previous page
+        #[arg(long)]
+        cursor: Option<String>,
+        /// Also show deposits in this fiat currency at the current price
+        /// (e.g., "usd", "eur")
+        #[arg(long)]
+        fiat: Option<String>,
+    },
+    /// Probe a balance against warning and critical floors, Nagios-style:
+    /// prints one status line with perfdata and exits 0 (OK), 1 (WARNING),
+    /// 2 (CRITICAL) or 3 (UNKNOWN)
+    Check {
+        /// NEAR account ID (e.g., "example.near")
+        account_id: String,
+        /// Warn if the balance is below this amount (e.g., "5" or "500 mNEAR")
+        #[arg(long)]
+        warn_below: Option<NearAmount>,
+        /// Critical if the balance is below this amount
+        #[arg(long)]
+        crit_below: Option<NearAmount>,
+    },
+    /// Show the status, actions, receipts and logs of a transaction
+    Tx {
+        /// Transaction hash
+        hash: String,
+        /// Account that signed the transaction (e.g., "example.near")
+        #[arg(long)]
+        signer: String,
+    },
+    /// Show staked and unstaked balances delegated to a staking pool
+    Staking {
+        /// NEAR account ID of the delegator (e.g., "example.near")
+        account_id: String,
+        /// Staking pool account ID (e.g., "example.poolv1.near")
+        #[arg(long)]
+        pool: String,
+    },
+    /// List the access keys of an account with their permissions
+    Keys {
+        /// NEAR account ID (e.g., "example.near")
+        account_id: String,
+    },
+    /// Sample the balance over a date range from the archival RPC
+    History {
+        /// NEAR account ID (e.g., "example.near")
+        account_id: String,
+        /// Start of the range: RFC 3339 or a date (start of that day, UTC)
+        #[arg(long, value_parser = utils::parse_start_datetime)]
+        from: DateTime<Utc>,
+        /// End of the range: RFC 3339 or a date (end of that day, UTC;
+        /// default: now)
+        #[arg(long, value_parser = utils::parse_datetime)]
+        to: Option<DateTime<Utc>>,
+        /// Interval between samples, e.g. "12h", "1d" or "1w"
+        #[arg(long, default_value = "1d", value_parser = utils::parse_step)]
+        step: TimeDelta,
+        /// Maximum concurrent samples fetched from the archival RPC
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
     },
 }
+
+/// Outgoing webhook options of the `monitor` and `bot` commands.
+///
+/// Every balance change is POSTed as JSON to each `--webhook` URL (see
+/// [`crate::webhook`]).
+#[derive(Args)]
+pub struct WebhookArgs {
+    /// POST balance changes to this URL; repeat or comma-separate for several
+    #[arg(long = "webhook", env = "NEAR_WEBHOOK_URLS", value_delimiter = ',')]
+    pub webhook_urls: Vec<String>,
+    /// Sign webhook bodies with HMAC-SHA256 using this secret
+    #[arg(long, env = "NEAR_WEBHOOK_SECRET", hide_env_values = true)]
+    pub webhook_secret: Option<String>,
+    /// Delivery attempts per webhook, including the first
+    #[arg(long, env = "NEAR_WEBHOOK_ATTEMPTS", default_value_t = 5)]
+    pub webhook_attempts: u32,
+    /// File that deliveries failing all attempts are appended to (JSON lines)
+    #[arg(
+        long,
+        env = "NEAR_WEBHOOK_DEAD_LETTER",
+        default_value = "webhook_dead_letter.jsonl"
+    )]
+    pub webhook_dead_letter: PathBuf,
+}
+
+/// Display modes of the `monitor` command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum MonitorView {
+    /// Print the account details on every reported change
+    Lines,
+    /// Redraw a table of all accounts after every poll
+    Table,
+}
+
+/// Output formats selected with the global `--output` flag.
+///
+/// The machine-readable formats are described in [`crate::output`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// JSON object, or array for lists (one object per line for `monitor`)
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
@@ -3,17 +3,49 @@
 //! This module contains the core execution logic for all CLI commands and modes.
 //! It handles:
 //! - One-time balance queries
-//! - Continuous monitoring with polling
+//! - Nagios-style balance checks
+//! - Continuous monitoring of one or more accounts with polling
 //! - Transaction history display
+//! - Transaction status and receipt drill-down
+//! - Staking pool delegation display
+//! - Access key inventory
+//! - Balance history export
+//! - Fiat valuation of balances and deposits
+//! - Machine-readable output (`--output`, see [`crate::output`])
+//! - Webhook notifications of balance changes
 //! - Telegram bot initialization
 
+use crate::alert::{AlertRules, Verdict};
+use crate::amount::{AmountFormat, NearAmount};
 use crate::bot;
-use crate::cli::{Cli, Commands};
-use crate::near::NearClient;
+use crate::check::{self, Thresholds};
+use crate::cli::{Cli, Commands, MonitorView, OutputFormat, WebhookArgs};
+use crate::error::Error;
+use crate::near::{
+    self, AccountSnapshot, BalanceSample, BlockReference, NearClient, TransactionFilter,
+};
+use crate::network::NetworkConfig;
+use crate::notify::MatrixConfig;
+use crate::output::{
+    self, AccessKeyRecord, BalanceRecord, CheckRecord, EventWriter, HistoryRecord, MonitorEvent,
+    StakingRecord, TokenBalanceRecord, TransactionRecord, TransactionStatusRecord,
+};
+use crate::price::{CoinGeckoPrices, PriceCache, PriceProvider, StaticPrices};
+use crate::retry::RetryPolicy;
 use crate::utils;
+use crate::webhook::{WebhookNotifier, WebhookPayload};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+use std::path::Path;
 use std::time::Duration;
 use tokio::time;
 
+/// Maximum number of samples a single `history` command may request.
+const MAX_HISTORY_SAMPLES: usize = 1000;
+
+/// Maximum number of causing transactions listed in a webhook payload.
+const MAX_WEBHOOK_TRANSACTIONS: usize = 10;
+
 /// Executes the CLI command specified in the parsed arguments.
 ///
 /// This is the main entry point for command execution. It routes to the
@@ -25,15 +57,15 @@ use tokio::time;
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on successful execution, or an error message describing the failure.
+/// Returns `Ok(())` on successful execution, or an [`Error`] describing the failure.
+/// `main` maps the error kind to the process exit code (see [`Error::exit_code`]).
 ///
 /// # Errors
 ///
-/// Returns `Err(String)` if:
-/// - Network requests fail
-/// - NEAR RPC returns an error
-/// - Account doesn't exist
-/// - Telegram bot token is invalid (for bot mode)
+/// Returns an [`Error`] if:
+/// - Network requests fail (`Transport`, `HttpStatus`)
+/// - NEAR RPC returns an error, e.g. the account doesn't exist (`Rpc`)
+/// - The network configuration is invalid (`Config`)
 ///
 /// # Examples
 ///
@@ -42,115 +74,441 @@ use tokio::time;
 /// # use near_balance_monitor::commands;
 /// # use clap::Parser;
 /// # #[tokio::main]
-/// # async fn main() -> Result<(), String> {
+/// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
 /// let cli = Cli::parse();
 /// commands::run(cli).await?;
 /// # Ok(())
 /// # }
 /// ```
-pub async fn run(cli: Cli) -> Result<(), String> {
+pub async fn run(cli: Cli) -> Result<(), Error> {
     let command_name = match &cli.command {
         Commands::Balance { .. } => "balance",
         Commands::Monitor { .. } => "monitor",
-        Commands::Bot => "bot",
+        Commands::Bot { .. } => "bot",
+        Commands::Check { .. } => "check",
         Commands::Txs { .. } => "txs",
+        Commands::Tx { .. } => "tx",
+        Commands::Staking { .. } => "staking",
+        Commands::Keys { .. } => "keys",
+        Commands::History { .. } => "history",
     };
     log::info!("Executing command={}", command_name);
 
-    let near_client = NearClient::new();
+    let network = NetworkConfig::resolve(
+        cli.network.network,
+        cli.network.rpc_url,
+        cli.network.archival_rpc_url,
+        cli.network.indexer_url,
+        cli.network.rpc_api_key,
+        cli.network.indexer_api_key,
+    )
+    .map_err(Error::Config)?;
+    let retry = RetryPolicy::new(
+        cli.retry.retry_attempts,
+        Duration::from_millis(cli.retry.retry_base_ms),
+        Duration::from_millis(cli.retry.retry_max_ms),
+        cli.retry.retry_jitter,
+    );
+    let near_client = NearClient::new(network).with_retry_policy(retry);
+    let provider: Box<dyn PriceProvider> = match &cli.price.price_file {
+        Some(path) => Box::new(StaticPrices::from_file(path)?),
+        None => Box::new(CoinGeckoPrices::new(
+            &cli.price.price_url,
+            cli.price.price_api_key,
+        )),
+    };
+    let prices = PriceCache::new(provider, Duration::from_secs(cli.price.price_ttl));
+    let format = cli.output;
 
     match cli.command {
-        Commands::Balance { account_id } => {
-            log::info!("Fetching balance account={}", account_id);
-            let balance = near_client.fetch_balance(&account_id).await?;
-            print_balance(&account_id, balance);
-        }
-        Commands::Monitor {
+        Commands::Balance {
             account_id,
-            interval,
+            token: Some(contract_id),
+            ..
         } => {
             log::info!(
-                "Monitor started account={} interval={}s",
+                "Fetching token balance account={} token={}",
                 account_id,
-                interval
+                contract_id
             );
-            println!("Monitoring {account_id} every {interval}s...");
-            let mut ticker = time::interval(Duration::from_secs(interval));
-            let mut previous_balance: Option<u128> = None;
-            let mut poll_count: u64 = 0;
-            let mut success_count: u64 = 0;
-            let mut error_count: u64 = 0;
-            let start_time = std::time::Instant::now();
-
-            loop {
-                ticker.tick().await;
-                poll_count += 1;
-                log::debug!(
-                    "Monitor poll account={} poll_count={}",
-                    account_id,
-                    poll_count
-                );
-
-                match near_client.fetch_balance(&account_id).await {
-                    Ok(balance) => {
-                        success_count += 1;
-                        let changed = previous_balance != Some(balance);
-                        if changed {
-                            log::info!(
-                                "Balance changed account={} old={:?} new={}",
-                                account_id,
-                                previous_balance,
-                                balance
-                            );
-                            print_balance(&account_id, balance);
-                            previous_balance = Some(balance);
-                        }
-                    }
-                    Err(e) => {
-                        error_count += 1;
-                        log::error!("Monitor fetch failed account={}: {}", account_id, e);
-                        eprintln!("[{}] Error: {e}", utils::now_timestamp());
+            let metadata = near_client.ft_metadata(&contract_id).await?;
+            let balance = near_client.ft_balance_of(&contract_id, &account_id).await?;
+            if format != OutputFormat::Text {
+                let record = TokenBalanceRecord::new(&account_id, &contract_id, &metadata, balance);
+                return output::print_record(format, &record);
+            }
+            println!(
+                "[{}] {} — {} ({}, {})",
+                utils::now_timestamp(),
+                account_id,
+                utils::format_token(balance, metadata.decimals, &metadata.symbol),
+                metadata.name,
+                contract_id
+            );
+        }
+        Commands::Balance {
+            account_id,
+            token: None,
+            at_block: None,
+            at: None,
+            fiat,
+        } => {
+            log::info!("Fetching balance account={}", account_id);
+            let account = near_client.fetch_account(&account_id).await?;
+            if format != OutputFormat::Text {
+                let mut record = BalanceRecord::new(&account_id, Utc::now(), &account);
+                if let Some(currency) = &fiat {
+                    if let Some(price) = prices.try_near_price(currency).await {
+                        record = record.with_fiat(currency, price);
                     }
                 }
-
-                if poll_count % 10 == 0 {
-                    log::info!(
-                        "Monitor heartbeat account={} uptime_secs={} polls={} success={} errors={}",
-                        account_id,
-                        start_time.elapsed().as_secs(),
-                        poll_count,
-                        success_count,
-                        error_count
-                    );
+                return output::print_record(format, &record);
+            }
+            print_account(&utils::now_timestamp(), &account_id, &account);
+            if let Some(currency) = &fiat {
+                print_fiat(&prices, currency, account.amount).await;
+            }
+            print_lockup(&near_client, &account_id).await;
+        }
+        Commands::Balance {
+            account_id,
+            token: None,
+            at_block: Some(block_id),
+            ..
+        } => {
+            let block = BlockReference::from_block_id(&block_id);
+            print_balance_at(&near_client, format, &account_id, &block).await?;
+        }
+        Commands::Balance {
+            account_id,
+            token: None,
+            at: Some(at),
+            ..
+        } => {
+            print_balance_at(
+                &near_client,
+                format,
+                &account_id,
+                &BlockReference::Timestamp(at),
+            )
+            .await?;
+        }
+        Commands::Monitor {
+            account_ids,
+            accounts_file,
+            interval,
+            view,
+            concurrency,
+            fiat,
+            min_delta,
+            min_percent,
+            below,
+            above,
+            direction,
+            webhook,
+        } => {
+            let rules = AlertRules {
+                min_delta: min_delta.map(|amount| amount.as_yocto()),
+                min_percent,
+                below: below.map(|amount| amount.as_yocto()),
+                above: above.map(|amount| amount.as_yocto()),
+                direction,
+            };
+            let mut requested = account_ids;
+            if let Some(path) = &accounts_file {
+                requested.extend(read_accounts_file(path)?);
+            }
+            let mut account_ids: Vec<String> = Vec::new();
+            for account_id in requested {
+                if !account_ids.contains(&account_id) {
+                    account_ids.push(account_id);
                 }
             }
+            if account_ids.is_empty() {
+                return Err(Error::Config("no accounts to monitor".to_string()));
+            }
+            log::info!(
+                "Monitor started accounts={} interval={}s view={:?} rules={:?}",
+                account_ids.len(),
+                interval,
+                view,
+                rules
+            );
+            let banner = format!(
+                "Monitoring {} every {interval}s, reporting {}...",
+                account_ids.join(", "),
+                rules.describe(utils::format_near)
+            );
+            // Keep stdout parseable in the machine-readable formats.
+            if format == OutputFormat::Text {
+                println!("{banner}");
+            } else {
+                eprintln!("{banner}");
+            }
+            let monitor = Monitor {
+                near_client: near_client.with_concurrency(concurrency),
+                prices,
+                account_ids,
+                interval,
+                view,
+                format,
+                fiat,
+                rules,
+                webhook: webhook_notifier(webhook),
+            };
+            tokio::select! {
+                () = monitor.run() => {}
+                _ = tokio::signal::ctrl_c() => log::info!("Monitor interrupted, shutting down"),
+            }
+            if let Some(webhook) = &monitor.webhook {
+                webhook.shutdown().await;
+            }
         }
-        Commands::Bot => {
-            log::info!("Starting Telegram bot mode");
-            bot::run().await?;
+        Commands::Bot {
+            concurrency,
+            webhook,
+            matrix_homeserver,
+            matrix_token,
+            matrix_rooms,
+        } => {
+            log::info!("Starting Telegram bot mode concurrency={}", concurrency);
+            let matrix = match (matrix_homeserver, matrix_token) {
+                (Some(homeserver), Some(access_token)) => Some(MatrixConfig {
+                    homeserver,
+                    access_token,
+                    rooms: matrix_rooms,
+                }),
+                _ => None,
+            };
+            bot::run(
+                near_client.with_concurrency(concurrency),
+                prices,
+                webhook_notifier(webhook),
+                matrix,
+            )
+            .await?;
         }
-        Commands::Txs { account_id } => {
-            log::info!("Fetching transactions account={}", account_id);
-            let txs = near_client.fetch_transactions(&account_id).await?;
-            if txs.is_empty() {
+        Commands::Txs {
+            account_id,
+            limit,
+            since,
+            until,
+            direction,
+            counterparty,
+            min_amount,
+            cursor,
+            fiat,
+        } => {
+            let filter = TransactionFilter {
+                since,
+                until,
+                direction,
+                counterparty,
+                min_amount,
+            };
+            log::info!(
+                "Fetching transactions account={} limit={} filter={:?}",
+                account_id,
+                limit,
+                filter
+            );
+            let page = near_client
+                .fetch_transactions(&account_id, &filter, limit, cursor.as_deref())
+                .await?;
+            if format != OutputFormat::Text {
+                let price = match &fiat {
+                    Some(currency) => prices.try_near_price(currency).await,
+                    None => None,
+                };
+                let mut records = Vec::with_capacity(page.transactions.len());
+                for tx in &page.transactions {
+                    let token = near_client.transaction_token(tx).await;
+                    let record = TransactionRecord::new(tx, token.as_ref());
+                    records.push(match (&fiat, price) {
+                        (Some(currency), Some(price)) => record.with_fiat(currency, price),
+                        _ => record,
+                    });
+                }
+                output::print_records(format, &records)?;
+                if let Some(cursor) = page.cursor {
+                    if page.page_limit_reached {
+                        eprintln!("{}", page_limit_note(&cursor));
+                    } else {
+                        eprintln!("More transactions available: repeat with --cursor {cursor}");
+                    }
+                }
+                return Ok(());
+            }
+            if page.transactions.is_empty() && page.page_limit_reached {
+                log::warn!(
+                    "No transactions found before page limit account={}",
+                    account_id
+                );
+            } else if page.transactions.is_empty() {
                 log::warn!("No transactions found account={}", account_id);
                 println!("No transactions found for {account_id}");
             } else {
                 log::info!(
                     "Displaying transactions account={} count={}",
                     account_id,
-                    txs.len()
+                    page.transactions.len()
                 );
+                let price = match &fiat {
+                    Some(currency) => prices
+                        .try_near_price(currency)
+                        .await
+                        .map(|price| (currency, price)),
+                    None => None,
+                };
                 println!("Last transactions for {account_id}:");
-                for tx in txs {
+                for tx in page.transactions {
+                    let token = near_client.transaction_token(&tx).await;
+                    let deposit = tx.actions_agg.deposit;
+                    let value = match price {
+                        Some((currency, price)) => format!(
+                            " ({})",
+                            utils::format_near_fiat(deposit.as_yocto(), price, currency)
+                        ),
+                        None => String::new(),
+                    };
                     println!(
-                        "- Time:   {}\n  Hash:   {}\n  From:   {}\n  To:     {}\n  Amount: {}\n",
-                        utils::format_timestamp(tx.block_timestamp),
+                        "- Time:   {}\n  Hash:   {}\n  From:   {}\n  To:     {}\n  Amount: {}{}",
+                        utils::format_timestamp(tx.block_timestamp.clone()),
                         tx.hash,
                         tx.signer_id,
                         tx.receiver_id,
-                        utils::format_near(tx.actions_agg.deposit as u128)
+                        deposit,
+                        value
+                    );
+                    for action in &tx.actions {
+                        println!(
+                            "  Action: {}",
+                            utils::describe_action(action, &tx.receiver_id, token.as_ref())
+                        );
+                    }
+                    println!();
+                }
+            }
+            if let Some(cursor) = page.cursor {
+                if page.page_limit_reached {
+                    println!("{}", page_limit_note(&cursor));
+                } else {
+                    println!("More transactions available: repeat with --cursor {cursor}");
+                }
+            }
+        }
+        Commands::Check {
+            account_id,
+            warn_below,
+            crit_below,
+        } => {
+            let thresholds = Thresholds {
+                warn_below: warn_below.map(|amount| amount.as_yocto()),
+                crit_below: crit_below.map(|amount| amount.as_yocto()),
+            };
+            log::info!(
+                "Checking balance account={} thresholds={:?}",
+                account_id,
+                thresholds
+            );
+            // Invalid floors are reported as UNKNOWN like any other failure
+            // to evaluate the check.
+            let fetched = match thresholds.validate() {
+                Ok(()) => near_client.fetch_account(&account_id).await,
+                Err(e) => Err(e),
+            };
+            let result = match fetched {
+                Ok(account) => check::check_account(&account_id, &account, &thresholds),
+                Err(e) => {
+                    log::warn!(
+                        "Check fetch failed account={} kind={}: {}",
+                        account_id,
+                        e.kind(),
+                        e
                     );
+                    check::check_error(&account_id, &e)
+                }
+            };
+            if format == OutputFormat::Text {
+                println!("{}", result.line);
+            } else {
+                output::print_record(format, &CheckRecord::new(&account_id, &result, &thresholds))?;
+            }
+            log::info!(
+                "Check completed account={} status={:?}",
+                account_id,
+                result.status
+            );
+            // The status is reported through the exit code, which
+            // monitoring systems read instead of an error message.
+            std::process::exit(result.status.exit_code());
+        }
+        Commands::Tx { hash, signer } => {
+            log::info!(
+                "Fetching transaction status hash={} signer={}",
+                hash,
+                signer
+            );
+            let status = near_client.fetch_transaction_status(&hash, &signer).await?;
+            if format != OutputFormat::Text {
+                return output::print_record(format, &TransactionStatusRecord::from(&status));
+            }
+            println!("{}", utils::format_transaction_status(&status));
+        }
+        Commands::Staking { account_id, pool } => {
+            log::info!("Fetching staking account={} pool={}", account_id, pool);
+            let staking = near_client.fetch_staking(&pool, &account_id).await?;
+            if format != OutputFormat::Text {
+                let record = StakingRecord::new(&account_id, &pool, &staking);
+                return output::print_record(format, &record);
+            }
+            println!("[{}] {} @ {}", utils::now_timestamp(), account_id, pool);
+            for line in utils::format_staking_details(&staking).lines() {
+                println!("  {line}");
+            }
+        }
+        Commands::Keys { account_id } => {
+            log::info!("Fetching access keys account={}", account_id);
+            let keys = near_client.fetch_access_keys(&account_id).await?;
+            if format != OutputFormat::Text {
+                let records: Vec<AccessKeyRecord> =
+                    keys.iter().map(AccessKeyRecord::from).collect();
+                return output::print_records(format, &records);
+            }
+            if keys.is_empty() {
+                println!("No access keys found for {account_id}");
+            } else {
+                println!("Access keys for {account_id}:");
+                for key in &keys {
+                    println!("- {}", utils::format_access_key(key));
+                }
+            }
+        }
+        Commands::History {
+            account_id,
+            from,
+            to,
+            step,
+            concurrency,
+        } => {
+            let times = sample_times(from, to.unwrap_or_else(Utc::now), step)?;
+            log::info!(
+                "Fetching balance history account={} from={} samples={} step={}",
+                account_id,
+                from,
+                times.len(),
+                step
+            );
+            let samples = near_client
+                .with_concurrency(concurrency)
+                .fetch_balance_history(&account_id, &times)
+                .await?;
+            match format {
+                OutputFormat::Text => print_history(&samples),
+                _ => {
+                    let records: Vec<HistoryRecord> =
+                        samples.iter().map(HistoryRecord::from).collect();
+                    output::print_records(format, &records)?;
                 }
             }
         }
@@ -161,11 +519,12 @@ pub async fn run(cli: Cli) -> Result<(), String> {
 
 /// Prints a formatted balance message with timestamp.
 ///
-/// Outputs the balance in a human-readable format with the current timestamp
-/// and account ID.
+/// Outputs the balance in a human-readable format with the given timestamp
+/// (the current time, or the block time for historical balances) and account ID.
 ///
 /// # Arguments
 ///
+/// * `timestamp` - Formatted time shown in brackets
 /// * `account_id` - The NEAR account ID
 /// * `balance` - The balance in yoctoNEAR
 ///
@@ -178,11 +537,548 @@ pub async fn run(cli: Cli) -> Result<(), String> {
 /// // Output: [2026-02-15 10:30:45 PST] example.near — 1.0000 NEAR
 /// # }
 /// ```
-fn print_balance(account_id: &str, balance: u128) {
+fn print_balance(timestamp: &str, account_id: &str, balance: u128) {
     println!(
         "[{}] {} — {}",
-        utils::now_timestamp(),
+        timestamp,
         account_id,
         utils::format_near(balance)
     );
 }
+
+/// Prints the balance line followed by the full account breakdown.
+///
+/// The first line matches [`print_balance`]; the breakdown is indented below it.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() {
+/// // Output:
+/// // [2026-02-15 10:30:45 PST] example.near — 1.0000 NEAR
+/// //   Total:            1.0000 NEAR
+/// //   Staked/locked:    0.0000 NEAR
+/// //   Storage reserved: 0.0018 NEAR (182 bytes)
+/// //   Available:        0.9982 NEAR
+/// //   ...
+/// # }
+/// ```
+fn print_account(timestamp: &str, account_id: &str, account: &AccountSnapshot) {
+    print_balance(timestamp, account_id, account.amount);
+    for line in utils::format_account_details(account).lines() {
+        println!("  {line}");
+    }
+}
+
+/// Prints an account's balance at a past block, or as its JSON/CSV record.
+async fn print_balance_at(
+    near_client: &NearClient,
+    format: OutputFormat,
+    account_id: &str,
+    block: &BlockReference,
+) -> Result<(), Error> {
+    log::info!(
+        "Fetching historical balance account={} block={:?}",
+        account_id,
+        block
+    );
+    let header = near_client.fetch_block(block).await?;
+    log::info!(
+        "Resolved block height={} hash={} timestamp={}",
+        header.height,
+        header.hash,
+        header.timestamp
+    );
+    let account = near_client
+        .fetch_account_at(account_id, &BlockReference::Height(header.height))
+        .await?;
+    if format != OutputFormat::Text {
+        let time = DateTime::from_timestamp_nanos(header.timestamp as i64);
+        let record = BalanceRecord::new(account_id, time, &account);
+        return output::print_record(format, &record);
+    }
+    print_account(
+        &utils::format_timestamp(header.timestamp.to_string()),
+        account_id,
+        &account,
+    );
+    Ok(())
+}
+
+/// Explains a transaction page cut short by the indexer page limit.
+fn page_limit_note(cursor: &str) -> String {
+    format!(
+        "Stopped after reading {} pages of history; the results may be \
+         incomplete for this range. Continue with --cursor {cursor}",
+        near::MAX_TRANSACTION_PAGES
+    )
+}
+
+/// Prints the lockup breakdown below the account details, if the account is
+/// a lockup contract.
+///
+/// Failures are reported on stderr without failing the command, since the
+/// native balance has already been printed.
+async fn print_lockup(near_client: &NearClient, account_id: &str) {
+    if !near::is_lockup_account(account_id) {
+        return;
+    }
+    match near_client.fetch_lockup(account_id).await {
+        Ok(lockup) => {
+            println!("  Lockup:");
+            for line in utils::format_lockup_details(&lockup).lines() {
+                println!("    {line}");
+            }
+        }
+        Err(e) => {
+            log::warn!(
+                "Lockup fetch failed account={} kind={}: {}",
+                account_id,
+                e.kind(),
+                e
+            );
+            eprintln!("  Lockup details unavailable: {e}");
+        }
+    }
+}
+
+/// Reads account IDs from a file with one ID per line.
+///
+/// Blank lines and text after `#` are ignored.
+///
+/// # Errors
+///
+/// Returns a `Config` error if the file cannot be read.
+fn read_accounts_file(path: &Path) -> Result<Vec<String>, Error> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        Error::Config(format!(
+            "failed to read accounts file {}: {e}",
+            path.display()
+        ))
+    })?;
+    Ok(data
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// The `monitor` command: polls accounts on a shared interval and reports
+/// changes that pass the alert rules.
+struct Monitor {
+    /// Client used for polling, with the requested concurrency.
+    near_client: NearClient,
+    /// Price source for `--fiat`.
+    prices: PriceCache,
+    /// Monitored accounts, in display order.
+    account_ids: Vec<String>,
+    /// Polling interval in seconds.
+    interval: u64,
+    /// How changes are shown in text output.
+    view: MonitorView,
+    /// Output format; other than text, changes and errors are printed as
+    /// [`MonitorEvent`]s.
+    format: OutputFormat,
+    /// Fiat currency shown next to balances.
+    fiat: Option<String>,
+    /// Which changes are reported.
+    rules: AlertRules,
+    /// Webhooks notified of reported changes.
+    webhook: Option<WebhookNotifier>,
+}
+
+/// Per-account state of the `monitor` command.
+#[derive(Default)]
+struct MonitorEntry {
+    /// (amount, locked) of the last reported snapshot.
+    reported: Option<(u128, u128)>,
+    /// Balance seen in the latest successful poll.
+    balance: Option<u128>,
+    /// Last reported change as (decreased, amount).
+    last_delta: Option<(bool, u128)>,
+    /// Time of the last reported change.
+    last_change: Option<String>,
+    /// Time the reported balance was seen, to look up causing transactions.
+    reported_at: Option<DateTime<Utc>>,
+    /// Hashes of the transactions sent with the last webhook, which the next
+    /// one leaves out.
+    reported_transactions: Vec<String>,
+    /// Error of the latest poll, if it failed.
+    error: Option<String>,
+}
+
+impl Monitor {
+    /// Polls all accounts every `interval` seconds, forever.
+    async fn run(&self) {
+        let mut ticker = time::interval(Duration::from_secs(self.interval));
+        let mut entries: HashMap<&str, MonitorEntry> = self
+            .account_ids
+            .iter()
+            .map(|account_id| (account_id.as_str(), MonitorEntry::default()))
+            .collect();
+        let mut poll_count: u64 = 0;
+        let mut success_count: u64 = 0;
+        let mut error_count: u64 = 0;
+        let start_time = std::time::Instant::now();
+        let mut events = EventWriter::new(self.format);
+
+        loop {
+            ticker.tick().await;
+            poll_count += 1;
+            log::debug!(
+                "Monitor poll accounts={} poll_count={}",
+                self.account_ids.len(),
+                poll_count
+            );
+
+            let results = self.near_client.fetch_accounts(&self.account_ids).await;
+            for account_id in &self.account_ids {
+                let (Some(result), Some(entry)) = (
+                    results.get(account_id),
+                    entries.get_mut(account_id.as_str()),
+                ) else {
+                    continue;
+                };
+                match result {
+                    Ok(account) => {
+                        success_count += 1;
+                        entry.error = None;
+                        entry.balance = Some(account.amount);
+                        self.check(account_id, account, entry, &mut events).await;
+                    }
+                    Err(e) => {
+                        error_count += 1;
+                        log::error!(
+                            "Monitor fetch failed account={} kind={}: {}",
+                            account_id,
+                            e.kind(),
+                            e
+                        );
+                        entry.error = Some(e.user_message());
+                        if self.format != OutputFormat::Text {
+                            self.write_event(&mut events, &MonitorEvent::error(account_id, e));
+                        } else if self.view == MonitorView::Lines {
+                            eprintln!("[{}] {account_id}: Error: {e}", utils::now_timestamp());
+                        }
+                    }
+                }
+            }
+            if self.format == OutputFormat::Text && self.view == MonitorView::Table {
+                self.print_table(&entries).await;
+            }
+
+            if poll_count % 10 == 0 {
+                log::info!(
+                    "Monitor heartbeat accounts={} uptime_secs={} polls={} success={} errors={}",
+                    self.account_ids.len(),
+                    start_time.elapsed().as_secs(),
+                    poll_count,
+                    success_count,
+                    error_count
+                );
+            }
+        }
+    }
+
+    /// Applies the alert rules to a fetched snapshot, updating the entry
+    /// and printing reported changes in the lines view or as events.
+    async fn check(
+        &self,
+        account_id: &str,
+        account: &AccountSnapshot,
+        entry: &mut MonitorEntry,
+        events: &mut EventWriter,
+    ) {
+        let balance = (account.amount, account.locked);
+        // Without rules any change is reported, including the locked
+        // amount; rules look at the balance only.
+        let verdict = match entry.reported {
+            Some(previous) if previous == balance => Verdict::Ignore,
+            Some((previous, _)) if !self.rules.is_empty() => {
+                self.rules.evaluate(previous, account.amount)
+            }
+            _ => Verdict::Alert,
+        };
+        match verdict {
+            Verdict::Alert => {
+                log::info!(
+                    "Balance changed account={} old={:?} new={} locked={}",
+                    account_id,
+                    entry.reported.map(|(amount, _)| amount),
+                    account.amount,
+                    account.locked
+                );
+                if self.format != OutputFormat::Text {
+                    let previous = entry.reported.map(|(amount, _)| amount);
+                    let mut event = MonitorEvent::change(account_id, account, previous);
+                    if let Some(currency) = &self.fiat {
+                        if let Some(price) = self.prices.try_near_price(currency).await {
+                            event = event.with_fiat(currency, price);
+                        }
+                    }
+                    self.write_event(events, &event);
+                }
+                if let Some((previous, _)) = entry.reported {
+                    if previous != account.amount {
+                        entry.last_delta =
+                            Some((account.amount < previous, account.amount.abs_diff(previous)));
+                        if let Some(webhook) = &self.webhook {
+                            let transactions = self
+                                .causing_transactions(
+                                    account_id,
+                                    entry.reported_at,
+                                    &entry.reported_transactions,
+                                )
+                                .await;
+                            webhook.send(WebhookPayload::balance_change(
+                                account_id,
+                                None,
+                                previous,
+                                account.amount,
+                                transactions.clone(),
+                            ));
+                            entry.reported_transactions = transactions;
+                        }
+                    }
+                }
+                entry.reported_at = Some(Utc::now());
+                entry.last_change = Some(utils::now_timestamp());
+                entry.reported = Some(balance);
+                if self.format == OutputFormat::Text && self.view == MonitorView::Lines {
+                    print_account(&utils::now_timestamp(), account_id, account);
+                    if let Some(delta) = entry.last_delta {
+                        println!("  Change:           {}", format_delta(delta));
+                    }
+                    if let Some(currency) = &self.fiat {
+                        print_fiat(&self.prices, currency, account.amount).await;
+                    }
+                    print_lockup(&self.near_client, account_id).await;
+                }
+            }
+            Verdict::Ignore => {
+                if entry.reported != Some(balance) {
+                    log::debug!(
+                        "Balance change not reported account={} new={}",
+                        account_id,
+                        account.amount
+                    );
+                    entry.reported = Some(balance);
+                    entry.reported_at = Some(Utc::now());
+                }
+            }
+            // Keep the last reported balance as the reference.
+            Verdict::Accumulate => {}
+        }
+    }
+
+    /// Returns the hashes of the account's transactions since `since`,
+    /// newest first, for webhook payloads. Hashes in `reported` were already
+    /// sent and are left out.
+    ///
+    /// Lookup failures are logged and yield an empty list, so that the
+    /// change is still delivered.
+    async fn causing_transactions(
+        &self,
+        account_id: &str,
+        since: Option<DateTime<Utc>>,
+        reported: &[String],
+    ) -> Vec<String> {
+        let Some(since) = since else {
+            return Vec::new();
+        };
+        let filter = TransactionFilter {
+            since: Some(since),
+            ..TransactionFilter::default()
+        };
+        match self
+            .near_client
+            .fetch_transactions(account_id, &filter, MAX_WEBHOOK_TRANSACTIONS, None)
+            .await
+        {
+            Ok(page) => page
+                .transactions
+                .into_iter()
+                .map(|tx| tx.hash)
+                .filter(|hash| !reported.contains(hash))
+                .collect(),
+            Err(e) => {
+                log::warn!(
+                    "Webhook transactions unavailable account={} kind={}: {}",
+                    account_id,
+                    e.kind(),
+                    e
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// Prints a monitor event; serialization failures are logged, since the
+    /// monitor keeps running.
+    fn write_event(&self, events: &mut EventWriter, event: &MonitorEvent) {
+        if let Err(e) = events.write(event) {
+            log::error!(
+                "Monitor event output failed account={}: {}",
+                event.account_id,
+                e
+            );
+        }
+    }
+
+    /// Clears the terminal and prints a table with one row per account.
+    async fn print_table(&self, entries: &HashMap<&str, MonitorEntry>) {
+        let price = match &self.fiat {
+            Some(currency) => self
+                .prices
+                .try_near_price(currency)
+                .await
+                .map(|price| (currency, price)),
+            None => None,
+        };
+        let width = self
+            .account_ids
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or_default()
+            .max("Account".len());
+        // Clear the screen and move the cursor home.
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "[{}] {} accounts, every {}s",
+            utils::now_timestamp(),
+            self.account_ids.len(),
+            self.interval
+        );
+        println!(
+            "{:<width$}  {:>24}  {:>20}  {:<25}  Status",
+            "Account", "Balance", "Last change", "Changed at"
+        );
+        for account_id in &self.account_ids {
+            let Some(entry) = entries.get(account_id.as_str()) else {
+                continue;
+            };
+            let balance = match (entry.balance, price) {
+                (Some(balance), Some((currency, price))) => format!(
+                    "{} ({})",
+                    utils::format_near(balance),
+                    utils::format_near_fiat(balance, price, currency)
+                ),
+                (Some(balance), None) => utils::format_near(balance),
+                (None, _) => "-".to_string(),
+            };
+            println!(
+                "{:<width$}  {:>24}  {:>20}  {:<25}  {}",
+                account_id,
+                balance,
+                entry
+                    .last_delta
+                    .map(format_delta)
+                    .unwrap_or_else(|| "-".to_string()),
+                entry.last_change.as_deref().unwrap_or("-"),
+                entry
+                    .error
+                    .as_deref()
+                    .map_or("OK".to_string(), |e| format!("ERROR: {e}"))
+            );
+        }
+    }
+}
+
+/// Builds the webhook notifier from the command-line options, or `None`
+/// if no `--webhook` URL is given.
+fn webhook_notifier(args: WebhookArgs) -> Option<WebhookNotifier> {
+    WebhookNotifier::new(
+        args.webhook_urls,
+        args.webhook_secret,
+        RetryPolicy::new(
+            args.webhook_attempts,
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+            0.5,
+        ),
+        args.webhook_dead_letter,
+    )
+}
+
+/// Formats a change as a signed NEAR amount, e.g. "-0.5000 NEAR".
+fn format_delta((decreased, amount): (bool, u128)) -> String {
+    let sign = if decreased { "-" } else { "+" };
+    format!("{sign}{}", utils::format_near(amount))
+}
+
+/// Prints the fiat value of a balance below the account details.
+///
+/// # Examples
+///
+/// ```no_run
+/// # fn main() {
+/// // Output:
+/// //   Fiat value:       ≈ 3.45 USD (1 NEAR = 3.45 USD)
+/// # }
+/// ```
+async fn print_fiat(prices: &PriceCache, currency: &str, yocto: u128) {
+    match prices.try_near_price(currency).await {
+        Some(price) => println!(
+            "  Fiat value:       {} (1 NEAR = {})",
+            utils::format_near_fiat(yocto, price, currency),
+            utils::format_fiat(price, currency)
+        ),
+        None => eprintln!("  Fiat value unavailable for '{currency}'"),
+    }
+}
+
+/// Returns the sampling times `from, from + step, ...` up to `to`, which is
+/// capped at the current time.
+///
+/// # Errors
+///
+/// Returns a `Config` error if the range is empty or would need more than
+/// [`MAX_HISTORY_SAMPLES`] samples.
+fn sample_times(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step: TimeDelta,
+) -> Result<Vec<DateTime<Utc>>, Error> {
+    let to = to.min(Utc::now());
+    if from > to {
+        return Err(Error::Config(format!(
+            "--from {from} is after the end of the range {to}"
+        )));
+    }
+    let mut times = Vec::new();
+    let mut at = from;
+    while at <= to {
+        if times.len() == MAX_HISTORY_SAMPLES {
+            return Err(Error::Config(format!(
+                "the range needs more than {MAX_HISTORY_SAMPLES} samples; use a larger --step"
+            )));
+        }
+        times.push(at);
+        at += step;
+    }
+    Ok(times)
+}
+
+/// Prints a balance history as a table.
+fn print_history(samples: &[BalanceSample]) {
+    println!(
+        "{:<25}  {:>12}  {:<25}  {:>20}",
+        "Time (UTC)", "Block", "Block time", "Balance"
+    );
+    for sample in samples {
+        let balance = match &sample.account {
+            Some(account) => NearAmount::from_yocto(account.amount)
+                .format(&AmountFormat::default().with_separators()),
+            None => "(no account)".to_string(),
+        };
+        println!(
+            "{:<25}  {:>12}  {:<25}  {:>20}",
+            sample.at.format("%Y-%m-%d %H:%M:%S"),
+            sample.block.height,
+            utils::format_timestamp(sample.block.timestamp.to_string()),
+            balance
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! RPC endpoint pool with health scoring.
+//!
+//! `NearClient` can be configured with several RPC endpoints. The pool keeps
+//! per-endpoint latency and error statistics, orders endpoints by a health
+//! score before each request, and puts failing endpoints into a cooldown so
+//! that requests rotate away from them during a provider outage.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Cooldown applied after the first consecutive failure.
+const BASE_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Upper bound for the cooldown of a repeatedly failing endpoint.
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Latency assumed for endpoints that have not served a request yet.
+const DEFAULT_LATENCY_MS: f64 = 500.0;
+
+/// Smoothing factor for the latency moving average.
+const LATENCY_EWMA_ALPHA: f64 = 0.3;
+
+/// Health statistics for a single endpoint.
+#[derive(Default)]
+struct EndpointHealth {
+    /// Exponentially weighted moving average of request latency in milliseconds.
+    latency_ewma_ms: Option<f64>,
+    /// Total number of successful requests.
+    successes: u64,
+    /// Total number of failed requests.
+    failures: u64,
+    /// Failures since the last success.
+    consecutive_failures: u32,
+    /// The endpoint is skipped until this instant unless no other is available.
+    cooldown_until: Option<Instant>,
+}
+
+impl EndpointHealth {
+    /// Fraction of failed requests, `0.0` when nothing has been recorded yet.
+    fn error_rate(&self) -> f64 {
+        let total = self.successes + self.failures;
+        if total == 0 {
+            0.0
+        } else {
+            self.failures as f64 / total as f64
+        }
+    }
+
+    /// Health score, lower is better.
+    ///
+    /// Latency is penalized by the error rate so that a fast but flaky
+    /// endpoint ranks below a slightly slower reliable one.
+    fn score(&self) -> f64 {
+        let latency = self.latency_ewma_ms.unwrap_or(DEFAULT_LATENCY_MS);
+        latency * (1.0 + 4.0 * self.error_rate())
+    }
+
+    /// Returns `true` if the endpoint is cooling down at `now`.
+    fn in_cooldown(&self, now: Instant) -> bool {
+        self.cooldown_until.is_some_and(|until| until > now)
+    }
+}
+
+/// A single RPC endpoint and its health statistics.
+struct Endpoint {
+    /// Endpoint URL.
+    url: String,
+    /// Mutable health statistics.
+    health: Mutex<EndpointHealth>,
+}
+
+/// A set of RPC endpoints ordered by health.
+///
+/// All methods take `&self`; health statistics are protected by a mutex so
+/// the pool can be shared between the bot's background task and command
+/// handlers.
+pub struct EndpointPool {
+    /// Configured endpoints in their original order.
+    endpoints: Vec<Endpoint>,
+}
+
+impl EndpointPool {
+    /// Creates a pool from a list of endpoint URLs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::endpoints::EndpointPool;
+    /// let pool = EndpointPool::new(vec!["https://rpc.mainnet.near.org".to_string()]);
+    /// assert_eq!(pool.candidates(), vec![0]);
+    /// ```
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    url,
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the URL of the endpoint at `index`.
+    pub fn url(&self, index: usize) -> &str {
+        &self.endpoints[index].url
+    }
+
+    /// Returns endpoint indices in the order they should be tried.
+    ///
+    /// Healthy endpoints come first, ordered by score. Endpoints in cooldown
+    /// follow, ordered by how soon their cooldown expires, so a request is
+    /// still attempted when every endpoint is failing.
+    pub fn candidates(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut healthy = Vec::new();
+        let mut cooling = Vec::new();
+
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            let health = endpoint.health.lock().unwrap_or_else(|e| e.into_inner());
+            if health.in_cooldown(now) {
+                cooling.push((index, health.cooldown_until));
+            } else {
+                healthy.push((index, health.score()));
+            }
+        }
+
+        healthy.sort_by(|a, b| a.1.total_cmp(&b.1));
+        cooling.sort_by_key(|&(_, until)| until);
+
+        healthy
+            .into_iter()
+            .map(|(index, _)| index)
+            .chain(cooling.into_iter().map(|(index, _)| index))
+            .collect()
+    }
+
+    /// Records a successful request served by the endpoint at `index`.
+    ///
+    /// Updates the latency average and clears any cooldown.
+    pub fn record_success(&self, index: usize, latency: Duration) {
+        let endpoint = &self.endpoints[index];
+        let mut health = endpoint.health.lock().unwrap_or_else(|e| e.into_inner());
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        health.latency_ewma_ms = Some(match health.latency_ewma_ms {
+            Some(avg) => avg + LATENCY_EWMA_ALPHA * (latency_ms - avg),
+            None => latency_ms,
+        });
+        health.successes += 1;
+        if health.consecutive_failures > 0 {
+            log::info!(
+                "RPC endpoint recovered endpoint={} after_failures={}",
+                endpoint.url,
+                health.consecutive_failures
+            );
+        }
+        health.consecutive_failures = 0;
+        health.cooldown_until = None;
+    }
+
+    /// Records a failed request against the endpoint at `index`.
+    ///
+    /// The endpoint is put into a cooldown that doubles with every
+    /// consecutive failure, up to five minutes.
+    pub fn record_failure(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        let mut health = endpoint.health.lock().unwrap_or_else(|e| e.into_inner());
+        health.failures += 1;
+        health.consecutive_failures += 1;
+
+        let exponent = (health.consecutive_failures - 1).min(8);
+        let cooldown = BASE_COOLDOWN
+            .saturating_mul(1 << exponent)
+            .min(MAX_COOLDOWN);
+        health.cooldown_until = Some(Instant::now() + cooldown);
+
+        log::warn!(
+            "RPC endpoint failure endpoint={} consecutive_failures={} error_rate={:.2} cooldown_secs={}",
+            endpoint.url,
+            health.consecutive_failures,
+            health.error_rate(),
+            cooldown.as_secs()
+        );
+    }
+}
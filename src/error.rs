@@ -0,0 +1,181 @@
+//! Error types shared across the crate.
+//!
+//! [`Error`] distinguishes the ways a request can fail so that callers can
+//! react differently: the bot tells the user that an account does not exist,
+//! and the CLI maps each kind to its own process exit code.
+
+/// Process exit code for invalid configuration or arguments.
+///
+/// Not 2, which clap uses for command-line usage errors.
+pub const EXIT_CONFIG: i32 = 9;
+/// Process exit code when the requested account does not exist.
+pub const EXIT_ACCOUNT_NOT_FOUND: i32 = 3;
+/// Process exit code for network failures (connection errors, HTTP errors).
+pub const EXIT_NETWORK: i32 = 4;
+/// Process exit code when a provider rate-limited the request.
+pub const EXIT_RATE_LIMITED: i32 = 5;
+/// Process exit code for other errors reported by the NEAR RPC.
+pub const EXIT_RPC: i32 = 6;
+/// Process exit code for responses that could not be parsed.
+pub const EXIT_PARSE: i32 = 7;
+/// Process exit code for NearBlocks indexer errors.
+pub const EXIT_INDEXER: i32 = 8;
+/// Process exit code when output could not be written.
+pub const EXIT_IO: i32 = 10;
+
+/// Errors returned by `NearClient` and the command layer.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    /// The HTTP request could not be sent or no response was received.
+    #[error("HTTP request failed: {0}")]
+    Transport(String),
+    /// The server answered with a non-success HTTP status.
+    #[error("HTTP status {status} from {url}")]
+    HttpStatus {
+        /// HTTP status code.
+        status: u16,
+        /// URL that returned the status.
+        url: String,
+    },
+    /// The NEAR RPC returned a JSON-RPC error.
+    #[error("RPC error {cause}: {message}")]
+    Rpc {
+        /// Top-level error name, e.g. `HANDLER_ERROR`.
+        name: String,
+        /// Error cause name, e.g. `UNKNOWN_ACCOUNT`. Falls back to `name`.
+        cause: String,
+        /// Human-readable description from the node.
+        message: String,
+    },
+    /// A response could not be parsed.
+    #[error("Failed to parse response: {0}")]
+    Parse(String),
+    /// The NearBlocks indexer returned an error.
+    #[error("Indexer error: {0}")]
+    Indexer(String),
+    /// Invalid configuration or user input.
+    #[error("{0}")]
+    Config(String),
+    /// Output could not be written.
+    #[error("I/O error: {0}")]
+    Io(String),
+}
+
+impl Error {
+    /// Builds an [`Error::Rpc`] from a NEAR JSON-RPC error object.
+    ///
+    /// NEAR errors look like
+    /// `{"name": "HANDLER_ERROR", "cause": {"name": "UNKNOWN_ACCOUNT", ...}, "data": "..."}`.
+    pub fn from_rpc_error(error: &serde_json::Value) -> Self {
+        let name = error
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or("UNKNOWN_ERROR")
+            .to_string();
+        let cause = error
+            .get("cause")
+            .and_then(|c| c.get("name"))
+            .and_then(|n| n.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| name.clone());
+        let message = error
+            .get("data")
+            .and_then(|d| d.as_str())
+            .or_else(|| error.get("message").and_then(|m| m.as_str()))
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        Error::Rpc {
+            name,
+            cause,
+            message,
+        }
+    }
+
+    /// Returns the RPC cause name, if this is an RPC error.
+    pub fn rpc_cause(&self) -> Option<&str> {
+        match self {
+            Error::Rpc { cause, .. } => Some(cause),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the RPC reported that the account does not exist.
+    pub fn is_account_not_found(&self) -> bool {
+        self.rpc_cause() == Some("UNKNOWN_ACCOUNT")
+    }
+
+    /// Returns `true` if the RPC does not know the requested transaction.
+    pub fn is_unknown_transaction(&self) -> bool {
+        self.rpc_cause() == Some("UNKNOWN_TRANSACTION")
+    }
+
+    /// Returns `true` if the RPC does not know the requested block, e.g.
+    /// because no block was produced at that height.
+    pub fn is_unknown_block(&self) -> bool {
+        self.rpc_cause() == Some("UNKNOWN_BLOCK")
+    }
+
+    /// Returns `true` if the provider rejected the request due to rate limiting.
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Error::HttpStatus { status: 429, .. })
+    }
+
+    /// Returns `true` for failures to reach the RPC or indexer.
+    pub fn is_network(&self) -> bool {
+        matches!(self, Error::Transport(_) | Error::HttpStatus { .. })
+    }
+
+    /// Returns a short machine-friendly name of the error kind, for log lines.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Transport(_) => "transport",
+            Error::HttpStatus { .. } => "http_status",
+            Error::Rpc { .. } => "rpc",
+            Error::Parse(_) => "parse",
+            Error::Indexer(_) => "indexer",
+            Error::Config(_) => "config",
+            Error::Io(_) => "io",
+        }
+    }
+
+    /// Returns the process exit code for this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::error::{Error, EXIT_NETWORK};
+    /// let error = Error::Transport("connection refused".to_string());
+    /// assert_eq!(error.exit_code(), EXIT_NETWORK);
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        if self.is_account_not_found() {
+            EXIT_ACCOUNT_NOT_FOUND
+        } else if self.is_rate_limited() {
+            EXIT_RATE_LIMITED
+        } else {
+            match self {
+                Error::Transport(_) | Error::HttpStatus { .. } => EXIT_NETWORK,
+                Error::Rpc { .. } => EXIT_RPC,
+                Error::Parse(_) => EXIT_PARSE,
+                Error::Indexer(_) => EXIT_INDEXER,
+                Error::Config(_) => EXIT_CONFIG,
+                Error::Io(_) => EXIT_IO,
+            }
+        }
+    }
+
+    /// Returns a short message suitable for showing to bot users.
+    pub fn user_message(&self) -> String {
+        if self.is_account_not_found() {
+            "That account doesn't exist.".to_string()
+        } else if self.is_unknown_transaction() {
+            "That transaction doesn't exist, or wasn't signed by that account.".to_string()
+        } else if self.is_rate_limited() {
+            "The NEAR provider is rate limiting requests, please try again later.".to_string()
+        } else if self.is_network() {
+            "The NEAR network is unreachable right now, please try again later.".to_string()
+        } else {
+            self.to_string()
+        }
+    }
+}
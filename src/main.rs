@@ -22,25 +22,52 @@
 //! near-monitor bot
 //! ```
 
+mod alert;
+mod amount;
 mod bot;
+mod check;
 mod cli;
 mod commands;
+mod endpoints;
+mod error;
 mod near;
+mod network;
+mod notify;
+mod output;
 mod persistence;
+mod price;
+mod retry;
 mod utils;
+mod webhook;
 
+use check::CheckStatus;
 use clap::Parser;
-use cli::Cli;
+use cli::{Cli, Commands};
 
 #[tokio::main]
 async fn main() {
     pretty_env_logger::init();
     log::info!("Application started version={}", env!("CARGO_PKG_VERSION"));
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Monitoring systems read clap's usage exit code 2 as CRITICAL; a
+        // check that cannot run is UNKNOWN.
+        Err(e) if e.use_stderr() && Cli::is_check_invocation(std::env::args_os()) => {
+            println!("NEAR {} - invalid arguments", CheckStatus::Unknown.label());
+            let _ = e.print();
+            std::process::exit(CheckStatus::Unknown.exit_code());
+        }
+        Err(e) => e.exit(),
+    };
+    let is_check = matches!(cli.command, Commands::Check { .. });
 
     if let Err(e) = commands::run(cli).await {
-        log::error!("Application error: {}", e);
+        log::error!("Application error kind={}: {}", e.kind(), e);
+        if is_check {
+            println!("NEAR {} - {e}", CheckStatus::Unknown.label());
+            std::process::exit(CheckStatus::Unknown.exit_code());
+        }
         eprintln!("Error: {e}");
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
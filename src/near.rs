@@ -2,30 +2,39 @@
 //!
 //! This module provides a client for interacting with the NEAR Protocol RPC API
 //! and NearBlocks API. It handles balance queries and transaction fetching.
+//! Endpoints are taken from a [`NetworkConfig`] profile.
 //!
 //! # Examples
 //!
 //! ```no_run
 //! use near_balance_monitor::near::NearClient;
+//! use near_balance_monitor::network::NetworkConfig;
 //!
 //! #[tokio::main]
-//! async fn main() -> Result<(), String> {
-//!     let client = NearClient::new();
+//! async fn main() -> Result<(), near_balance_monitor::error::Error> {
+//!     let client = NearClient::new(NetworkConfig::mainnet());
 //!     let balance = client.fetch_balance("example.near").await?;
 //!     println!("Balance: {} yoctoNEAR", balance);
 //!     Ok(())
 //! }
 //! ```
 
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-/// NEAR RPC endpoint URL.
-const NEAR_RPC_URL: &str = "https://h36uashbwvxlllkjfzzaxgfu-near-rpc.defuse.org";
-
-/// Conversion factor from yoctoNEAR to NEAR.
-/// 1 NEAR = 10^24 yoctoNEAR.
-pub const YOCTO_NEAR: f64 = 1e24;
+use crate::amount::NearAmount;
+use crate::endpoints::EndpointPool;
+use crate::error::Error;
+use crate::network::NetworkConfig;
+use crate::retry::{self, RetryPolicy};
 
 /// JSON-RPC request structure for NEAR RPC calls.
 #[derive(Serialize)]
@@ -38,16 +47,888 @@ struct RpcRequest {
 
 /// JSON-RPC response structure from NEAR RPC.
 #[derive(Deserialize)]
-struct RpcResponse {
-    result: Option<AccountView>,
+struct RpcResponse<T> {
+    result: Option<T>,
     error: Option<serde_json::Value>,
 }
 
+/// Outcome of a failed request against a single RPC endpoint.
+enum AttemptError {
+    /// The endpoint misbehaved; another endpoint may succeed.
+    Endpoint(Error),
+    /// The node reported a transient condition; retry after a backoff.
+    Transient(Error),
+    /// The request was rejected; retrying elsewhere would not help.
+    Request(Error),
+}
+
+/// Which set of RPC endpoints a request is sent to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RpcPool {
+    /// Regular endpoints, for queries at the latest final block.
+    Regular,
+    /// Archival endpoints, for queries at explicit past blocks.
+    Archival,
+}
+
+/// Reference to the block at which state is read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockReference {
+    /// The latest final block.
+    Final,
+    /// The block at the given height.
+    Height(u64),
+    /// The block with the given hash.
+    Hash(String),
+    /// The last block produced at or before the given time.
+    Timestamp(DateTime<Utc>),
+}
+
+impl BlockReference {
+    /// Parses a block ID given as a height or a base58 hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::near::BlockReference;
+    /// assert_eq!(BlockReference::from_block_id("120000000"), BlockReference::Height(120_000_000));
+    /// assert!(matches!(BlockReference::from_block_id("9f2V...hash"), BlockReference::Hash(_)));
+    /// ```
+    pub fn from_block_id(block_id: &str) -> Self {
+        match block_id.parse::<u64>() {
+            Ok(height) => BlockReference::Height(height),
+            Err(_) => BlockReference::Hash(block_id.to_string()),
+        }
+    }
+}
+
+/// Header fields of a block.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BlockHeader {
+    /// Block height.
+    pub height: u64,
+    /// Block hash.
+    pub hash: String,
+    /// Block timestamp in nanoseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Block returned by the NEAR RPC `block` method; only the header is used.
+#[derive(Deserialize)]
+struct BlockView {
+    header: BlockHeader,
+}
+
+/// Subset of the `EXPERIMENTAL_genesis_config` response.
+#[derive(Deserialize)]
+struct GenesisConfigView {
+    genesis_height: u64,
+}
+
+/// One sample of an account's balance history.
+#[derive(Debug, Clone)]
+pub struct BalanceSample {
+    /// Requested point in time.
+    pub at: DateTime<Utc>,
+    /// Last block produced at or before `at`.
+    pub block: BlockHeader,
+    /// Account state at `block`; `None` if the account did not exist yet.
+    pub account: Option<AccountSnapshot>,
+}
+
+/// Number of consecutive heights probed when a block was skipped.
+const MAX_SKIPPED_BLOCKS: u64 = 32;
+
 /// Account view returned by the NEAR RPC `view_account` method.
 #[derive(Deserialize)]
 struct AccountView {
     /// Account balance in yoctoNEAR as a string.
     amount: String,
+    /// Balance locked for staking in yoctoNEAR as a string.
+    locked: String,
+    /// Storage used by the account in bytes.
+    storage_usage: u64,
+    /// Hash of the deployed contract code (all ones when no contract is deployed).
+    code_hash: String,
+    /// Height of the block the view was taken at.
+    block_height: u64,
+    /// Hash of the block the view was taken at.
+    block_hash: String,
+}
+
+/// Subset of the `EXPERIMENTAL_protocol_config` response.
+#[derive(Deserialize)]
+struct ProtocolConfigView {
+    runtime_config: RuntimeConfigView,
+}
+
+/// Runtime parameters from the protocol config.
+#[derive(Deserialize)]
+struct RuntimeConfigView {
+    /// Storage staking cost per byte in yoctoNEAR as a string.
+    storage_amount_per_byte: String,
+}
+
+/// Storage staking price used when the protocol config cannot be fetched:
+/// 10^19 yoctoNEAR per byte (1 NEAR per 100 kB).
+const DEFAULT_STORAGE_PRICE_PER_BYTE: u128 = 10_000_000_000_000_000_000;
+
+/// How long the default storage price is used after the protocol config
+/// could not be reached, before it is fetched again.
+const STORAGE_PRICE_RETRY_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Cached storage staking price.
+#[derive(Clone, Copy)]
+struct CachedStoragePrice {
+    /// Price per byte in yoctoNEAR.
+    price: u128,
+    /// When to fetch the price again; `None` keeps it for the client's lifetime.
+    refresh_at: Option<Instant>,
+}
+
+/// Result of a `call_function` view query.
+#[derive(Deserialize)]
+struct CallFunctionView {
+    /// Raw bytes returned by the contract method.
+    result: Vec<u8>,
+}
+
+/// NEP-148 fungible token metadata returned by `ft_metadata`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct FtMetadata {
+    /// Human-readable token name, e.g. "USD Coin".
+    pub name: String,
+    /// Ticker symbol, e.g. "USDC".
+    pub symbol: String,
+    /// Number of decimals used to display balances.
+    pub decimals: u8,
+}
+
+/// Delegation state of an account in a staking pool.
+///
+/// Amounts are in yoctoNEAR. Unstaked funds become withdrawable a few epochs
+/// after `unstake`; `unstaked_available` reports whether that has happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakingBalance {
+    /// Balance currently staked with the pool, including accrued rewards.
+    pub staked: u128,
+    /// Balance unstaked (or deposited but not staked) in the pool.
+    pub unstaked: u128,
+    /// Whether the unstaked balance can be withdrawn now.
+    pub unstaked_available: bool,
+}
+
+impl StakingBalance {
+    /// Total balance held in the pool (staked + unstaked).
+    pub fn total(&self) -> u128 {
+        self.staked.saturating_add(self.unstaked)
+    }
+
+    /// Returns `true` if there is an unstaked balance that can be withdrawn.
+    pub fn is_withdrawable(&self) -> bool {
+        self.unstaked > 0 && self.unstaked_available
+    }
+}
+
+/// Vesting schedule of a lockup contract, as returned by `get_vesting_information`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Vesting {
+    /// The lockup has no vesting.
+    None,
+    /// The schedule is private; only its hash is stored on chain.
+    Hash(String),
+    /// Public vesting schedule. Timestamps are in nanoseconds.
+    Schedule {
+        /// When vesting starts.
+        start_timestamp: u64,
+        /// Nothing vests before the cliff.
+        cliff_timestamp: u64,
+        /// When everything is vested.
+        end_timestamp: u64,
+    },
+    /// Vesting was terminated by the foundation.
+    Terminating {
+        /// Unvested amount at the time of termination.
+        unvested_amount: u128,
+        /// Termination status, e.g. "VestingTerminatedWithDeficit".
+        status: String,
+    },
+}
+
+/// Raw `get_vesting_information` result; integers are JSON strings.
+#[derive(Deserialize)]
+enum VestingView {
+    None,
+    VestingHash(String),
+    VestingSchedule {
+        start_timestamp: String,
+        cliff_timestamp: String,
+        end_timestamp: String,
+    },
+    Terminating {
+        unvested_amount: String,
+        status: serde_json::Value,
+    },
+}
+
+impl TryFrom<VestingView> for Vesting {
+    type Error = Error;
+
+    fn try_from(view: VestingView) -> Result<Self, Error> {
+        let parse_u64 = |value: &str| {
+            value
+                .parse::<u64>()
+                .map_err(|e| Error::Parse(format!("invalid vesting timestamp {value}: {e}")))
+        };
+        Ok(match view {
+            VestingView::None => Vesting::None,
+            VestingView::VestingHash(hash) => Vesting::Hash(hash),
+            VestingView::VestingSchedule {
+                start_timestamp,
+                cliff_timestamp,
+                end_timestamp,
+            } => Vesting::Schedule {
+                start_timestamp: parse_u64(&start_timestamp)?,
+                cliff_timestamp: parse_u64(&cliff_timestamp)?,
+                end_timestamp: parse_u64(&end_timestamp)?,
+            },
+            VestingView::Terminating {
+                unvested_amount,
+                status,
+            } => Vesting::Terminating {
+                unvested_amount: unvested_amount.parse::<u128>().map_err(|e| {
+                    Error::Parse(format!("invalid unvested amount {unvested_amount}: {e}"))
+                })?,
+                status: status
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| status.to_string()),
+            },
+        })
+    }
+}
+
+/// State of a lockup contract (`*.lockup.near`).
+///
+/// The native balance of a lockup account includes tokens the owner cannot
+/// spend yet; these view-call results show what is actually available.
+/// Amounts are in yoctoNEAR.
+#[derive(Debug, Clone)]
+pub struct LockupInfo {
+    /// Account that owns the lockup (`get_owner_account_id`).
+    pub owner: String,
+    /// Total balance of the lockup, including tokens staked through it (`get_balance`).
+    pub balance: u128,
+    /// Amount still locked (`get_locked_amount`).
+    pub locked: u128,
+    /// Amount the owner can withdraw right now (`get_liquid_owners_balance`).
+    pub liquid: u128,
+    /// Amount not yet vested (`get_unvested_amount`).
+    pub unvested: u128,
+    /// Vesting schedule (`get_vesting_information`).
+    pub vesting: Vesting,
+    /// Staking pool selected by the lockup, if any (`get_staking_pool_account_id`).
+    pub staking_pool: Option<String>,
+}
+
+/// Returns `true` if the account ID looks like a lockup contract,
+/// e.g. `<hash>.lockup.near`.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::near::is_lockup_account;
+/// assert!(is_lockup_account("1a2b3c.lockup.near"));
+/// assert!(!is_lockup_account("lockup.near"));
+/// assert!(!is_lockup_account("example.near"));
+/// ```
+pub fn is_lockup_account(account_id: &str) -> bool {
+    let mut labels = account_id.rsplit('.');
+    labels.next();
+    labels.next() == Some("lockup") && labels.next().is_some()
+}
+
+/// Permission granted by an access key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AccessKeyPermission {
+    /// The key can sign any transaction on behalf of the account.
+    FullAccess,
+    /// The key can only call the given methods of one contract, without deposit.
+    FunctionCall {
+        /// Remaining gas allowance in yoctoNEAR; `None` means unlimited.
+        allowance: Option<u128>,
+        /// Contract the key may call.
+        receiver_id: String,
+        /// Methods the key may call; empty means any method.
+        method_names: Vec<String>,
+    },
+}
+
+/// An access key of an account, as listed by `view_access_key_list`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessKey {
+    /// Public key, e.g. "ed25519:6E8sCci9...".
+    pub public_key: String,
+    /// What the key is allowed to do.
+    pub permission: AccessKeyPermission,
+}
+
+/// Result of a `view_access_key_list` query.
+#[derive(Deserialize)]
+struct AccessKeyListView {
+    keys: Vec<AccessKeyInfoView>,
+}
+
+/// One entry of [`AccessKeyListView`].
+#[derive(Deserialize)]
+struct AccessKeyInfoView {
+    public_key: String,
+    access_key: AccessKeyView,
+}
+
+/// Access key body; the nonce is ignored.
+#[derive(Deserialize)]
+struct AccessKeyView {
+    permission: PermissionView,
+}
+
+/// Raw permission as returned by the RPC: `"FullAccess"` or
+/// `{"FunctionCall": {...}}` with the allowance as a string.
+#[derive(Deserialize)]
+enum PermissionView {
+    FullAccess,
+    FunctionCall {
+        allowance: Option<String>,
+        receiver_id: String,
+        method_names: Vec<String>,
+    },
+}
+
+impl PermissionView {
+    /// Converts the raw permission of a key on `account_id`, parsing the allowance.
+    fn into_permission(self, account_id: &str) -> Result<AccessKeyPermission, Error> {
+        Ok(match self {
+            PermissionView::FullAccess => AccessKeyPermission::FullAccess,
+            PermissionView::FunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+            } => AccessKeyPermission::FunctionCall {
+                allowance: allowance.map(|a| parse_yocto(&a, account_id)).transpose()?,
+                receiver_id,
+                method_names,
+            },
+        })
+    }
+}
+
+/// An action of a transaction or receipt.
+///
+/// Amounts are in yoctoNEAR.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Creates the receiver account.
+    CreateAccount,
+    /// Deploys a contract to the receiver account.
+    DeployContract {
+        /// Size of the contract code in bytes.
+        code_size: usize,
+    },
+    /// Calls a contract method.
+    FunctionCall {
+        /// Called method.
+        method_name: String,
+        /// Raw call arguments, usually JSON.
+        args: Vec<u8>,
+        /// Gas attached to the call.
+        gas: u64,
+        /// Deposit attached to the call.
+        deposit: u128,
+    },
+    /// Transfers NEAR to the receiver.
+    Transfer {
+        /// Transferred amount.
+        deposit: u128,
+    },
+    /// Stakes NEAR as a validator.
+    Stake {
+        /// New total stake.
+        stake: u128,
+        /// Validator key.
+        public_key: String,
+    },
+    /// Adds an access key to the receiver account.
+    AddKey(AccessKey),
+    /// Deletes an access key from the receiver account.
+    DeleteKey {
+        /// Deleted public key.
+        public_key: String,
+    },
+    /// Deletes the receiver account and sends its balance to the beneficiary.
+    DeleteAccount {
+        /// Account receiving the remaining balance.
+        beneficiary_id: String,
+    },
+    /// Meta transaction: actions signed by `sender_id` and relayed by the signer.
+    Delegate {
+        /// Account that signed the inner actions.
+        sender_id: String,
+        /// Receiver of the inner actions.
+        receiver_id: String,
+        /// Inner actions.
+        actions: Vec<Action>,
+    },
+    /// An action kind this client does not know, by name.
+    Other(String),
+}
+
+impl Action {
+    /// Parses an action as returned by the RPC, e.g. `"CreateAccount"` or
+    /// `{"Transfer": {"deposit": "1000"}}`. Unknown or malformed actions
+    /// become [`Action::Other`] so that one odd action doesn't hide the rest.
+    fn from_rpc(value: serde_json::Value) -> Self {
+        let name = match &value {
+            serde_json::Value::String(name) => name.clone(),
+            serde_json::Value::Object(map) => map.keys().next().cloned().unwrap_or_default(),
+            other => other.to_string(),
+        };
+        match serde_json::from_value::<ActionView>(value) {
+            Ok(view) => view.into_action().unwrap_or_else(|e| {
+                log::warn!("Failed to convert action kind={}: {}", name, e);
+                Action::Other(name)
+            }),
+            Err(_) => Action::Other(name),
+        }
+    }
+}
+
+/// Raw action as returned by the RPC; amounts are JSON strings and binary
+/// fields are base64.
+#[derive(Deserialize)]
+enum ActionView {
+    CreateAccount,
+    DeployContract {
+        code: String,
+    },
+    FunctionCall {
+        method_name: String,
+        args: String,
+        gas: u64,
+        deposit: String,
+    },
+    Transfer {
+        deposit: String,
+    },
+    Stake {
+        stake: String,
+        public_key: String,
+    },
+    AddKey {
+        public_key: String,
+        access_key: AccessKeyView,
+    },
+    DeleteKey {
+        public_key: String,
+    },
+    DeleteAccount {
+        beneficiary_id: String,
+    },
+    Delegate {
+        delegate_action: DelegateActionView,
+    },
+}
+
+/// Inner part of a signed meta transaction.
+#[derive(Deserialize)]
+struct DelegateActionView {
+    sender_id: String,
+    receiver_id: String,
+    actions: Vec<serde_json::Value>,
+}
+
+impl ActionView {
+    /// Converts the raw action, decoding amounts and base64 fields.
+    fn into_action(self) -> Result<Action, Error> {
+        let parse_amount = |value: &str| {
+            value
+                .parse::<u128>()
+                .map_err(|e| Error::Parse(format!("invalid action amount {value}: {e}")))
+        };
+        Ok(match self {
+            ActionView::CreateAccount => Action::CreateAccount,
+            ActionView::DeployContract { code } => Action::DeployContract {
+                code_size: BASE64
+                    .decode(&code)
+                    .map(|code| code.len())
+                    .unwrap_or(code.len() / 4 * 3),
+            },
+            ActionView::FunctionCall {
+                method_name,
+                args,
+                gas,
+                deposit,
+            } => Action::FunctionCall {
+                method_name,
+                args: BASE64.decode(&args).unwrap_or_else(|_| args.into_bytes()),
+                gas,
+                deposit: parse_amount(&deposit)?,
+            },
+            ActionView::Transfer { deposit } => Action::Transfer {
+                deposit: parse_amount(&deposit)?,
+            },
+            ActionView::Stake { stake, public_key } => Action::Stake {
+                stake: parse_amount(&stake)?,
+                public_key,
+            },
+            ActionView::AddKey {
+                public_key,
+                access_key,
+            } => Action::AddKey(AccessKey {
+                permission: access_key.permission.into_permission(&public_key)?,
+                public_key,
+            }),
+            ActionView::DeleteKey { public_key } => Action::DeleteKey { public_key },
+            ActionView::DeleteAccount { beneficiary_id } => {
+                Action::DeleteAccount { beneficiary_id }
+            }
+            ActionView::Delegate { delegate_action } => Action::Delegate {
+                sender_id: delegate_action.sender_id,
+                receiver_id: delegate_action.receiver_id,
+                actions: delegate_action
+                    .actions
+                    .into_iter()
+                    .map(Action::from_rpc)
+                    .collect(),
+            },
+        })
+    }
+}
+
+/// Result of executing a transaction or receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    /// Not executed yet.
+    Pending,
+    /// Succeeded and returned a value, shown as text when it is UTF-8 and
+    /// as base64 otherwise.
+    SuccessValue(String),
+    /// Succeeded by handing over to another receipt.
+    SuccessReceipt(String),
+    /// Failed with the given error.
+    Failure(String),
+}
+
+impl ExecutionStatus {
+    /// Parses a status such as `{"SuccessValue": "..."}`, `{"Failure": {...}}`
+    /// or `"NotStarted"`.
+    fn from_rpc(value: &serde_json::Value) -> Self {
+        if let Some(value) = value.get("SuccessValue").and_then(|v| v.as_str()) {
+            let text = BASE64
+                .decode(value)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            return ExecutionStatus::SuccessValue(text.unwrap_or_else(|| value.to_string()));
+        }
+        if let Some(id) = value.get("SuccessReceiptId").and_then(|v| v.as_str()) {
+            return ExecutionStatus::SuccessReceipt(id.to_string());
+        }
+        if let Some(failure) = value.get("Failure") {
+            return ExecutionStatus::Failure(describe_failure(failure));
+        }
+        ExecutionStatus::Pending
+    }
+
+    /// Returns `true` if execution failed.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, ExecutionStatus::Failure(_))
+    }
+}
+
+/// Turns a nested RPC error such as
+/// `{"ActionError": {"index": 0, "kind": {"FunctionCallError": {"ExecutionError": "..."}}}}`
+/// into `ActionError: FunctionCallError: ExecutionError: ...`.
+fn describe_failure(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(message) => message.clone(),
+        serde_json::Value::Object(map) => {
+            if let Some(kind) = map.get("kind") {
+                return describe_failure(kind);
+            }
+            match map.iter().next() {
+                Some((name, inner)) if map.len() == 1 => match inner {
+                    serde_json::Value::Object(_) | serde_json::Value::String(_) => {
+                        format!("{}: {}", name, describe_failure(inner))
+                    }
+                    serde_json::Value::Null => name.clone(),
+                    other => format!("{name}: {other}"),
+                },
+                _ => value.to_string(),
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Execution outcome of a transaction or one of its receipts.
+#[derive(Debug, Clone)]
+pub struct ExecutionOutcome {
+    /// Transaction hash or receipt ID.
+    pub id: String,
+    /// Account the transaction or receipt was executed on.
+    pub executor_id: String,
+    /// Gas burnt by the execution.
+    pub gas_burnt: u64,
+    /// Tokens burnt for the gas, in yoctoNEAR.
+    pub tokens_burnt: u128,
+    /// Logs emitted, e.g. NEP-297 events.
+    pub logs: Vec<String>,
+    /// Receipts produced by the execution.
+    pub receipt_ids: Vec<String>,
+    /// Execution result.
+    pub status: ExecutionStatus,
+}
+
+/// A receipt produced while executing a transaction.
+#[derive(Debug, Clone)]
+pub struct Receipt {
+    /// Receipt ID.
+    pub receipt_id: String,
+    /// Account that sent the receipt.
+    pub predecessor_id: String,
+    /// Account the receipt is executed on.
+    pub receiver_id: String,
+    /// Actions of the receipt; empty for data receipts.
+    pub actions: Vec<Action>,
+}
+
+/// Status of a transaction with all its receipts, from `EXPERIMENTAL_tx_status`.
+#[derive(Debug, Clone)]
+pub struct TransactionStatus {
+    /// Transaction hash.
+    pub hash: String,
+    /// Account that signed the transaction.
+    pub signer_id: String,
+    /// Account the transaction was sent to.
+    pub receiver_id: String,
+    /// Actions of the transaction.
+    pub actions: Vec<Action>,
+    /// Overall result, including failures of later receipts.
+    pub status: ExecutionStatus,
+    /// Outcome of converting the transaction into its first receipt.
+    pub transaction_outcome: ExecutionOutcome,
+    /// Outcomes of every receipt, in execution order.
+    pub receipts_outcome: Vec<ExecutionOutcome>,
+    /// Receipts with their actions, including refunds when the node reports them.
+    pub receipts: Vec<Receipt>,
+}
+
+impl TransactionStatus {
+    /// Total gas burnt by the transaction and its receipts.
+    pub fn gas_burnt(&self) -> u64 {
+        self.outcomes().map(|outcome| outcome.gas_burnt).sum()
+    }
+
+    /// Total tokens burnt for gas by the transaction and its receipts, in yoctoNEAR.
+    pub fn tokens_burnt(&self) -> u128 {
+        self.outcomes().map(|outcome| outcome.tokens_burnt).sum()
+    }
+
+    /// Every outcome, starting with the transaction's.
+    pub fn outcomes(&self) -> impl Iterator<Item = &ExecutionOutcome> {
+        std::iter::once(&self.transaction_outcome).chain(&self.receipts_outcome)
+    }
+
+    /// Returns the receipt with the given ID, if the node reported it.
+    pub fn receipt(&self, receipt_id: &str) -> Option<&Receipt> {
+        self.receipts
+            .iter()
+            .find(|receipt| receipt.receipt_id == receipt_id)
+    }
+}
+
+/// Raw `EXPERIMENTAL_tx_status` result.
+#[derive(Deserialize)]
+struct TransactionStatusView {
+    status: serde_json::Value,
+    transaction: SignedTransactionView,
+    transaction_outcome: ExecutionOutcomeWithIdView,
+    receipts_outcome: Vec<ExecutionOutcomeWithIdView>,
+    #[serde(default)]
+    receipts: Vec<ReceiptView>,
+}
+
+/// Transaction part of [`TransactionStatusView`].
+#[derive(Deserialize)]
+struct SignedTransactionView {
+    hash: String,
+    signer_id: String,
+    receiver_id: String,
+    actions: Vec<serde_json::Value>,
+}
+
+/// Outcome with the ID of the transaction or receipt it belongs to.
+#[derive(Deserialize)]
+struct ExecutionOutcomeWithIdView {
+    id: String,
+    outcome: ExecutionOutcomeView,
+}
+
+/// Raw execution outcome; `tokens_burnt` is a JSON string.
+#[derive(Deserialize)]
+struct ExecutionOutcomeView {
+    executor_id: String,
+    gas_burnt: u64,
+    tokens_burnt: String,
+    logs: Vec<String>,
+    receipt_ids: Vec<String>,
+    status: serde_json::Value,
+}
+
+/// Receipt as listed by `EXPERIMENTAL_tx_status`.
+#[derive(Deserialize)]
+struct ReceiptView {
+    receipt_id: String,
+    predecessor_id: String,
+    receiver_id: String,
+    receipt: serde_json::Value,
+}
+
+impl TryFrom<ExecutionOutcomeWithIdView> for ExecutionOutcome {
+    type Error = Error;
+
+    fn try_from(view: ExecutionOutcomeWithIdView) -> Result<Self, Error> {
+        let outcome = view.outcome;
+        Ok(ExecutionOutcome {
+            tokens_burnt: outcome.tokens_burnt.parse::<u128>().map_err(|e| {
+                Error::Parse(format!(
+                    "invalid tokens burnt {}: {e}",
+                    outcome.tokens_burnt
+                ))
+            })?,
+            id: view.id,
+            executor_id: outcome.executor_id,
+            gas_burnt: outcome.gas_burnt,
+            logs: outcome.logs,
+            receipt_ids: outcome.receipt_ids,
+            status: ExecutionStatus::from_rpc(&outcome.status),
+        })
+    }
+}
+
+impl TryFrom<TransactionStatusView> for TransactionStatus {
+    type Error = Error;
+
+    fn try_from(view: TransactionStatusView) -> Result<Self, Error> {
+        let receipts = view
+            .receipts
+            .into_iter()
+            .map(|receipt| {
+                let actions = receipt
+                    .receipt
+                    .get("Action")
+                    .and_then(|action| action.get("actions"))
+                    .and_then(|actions| actions.as_array())
+                    .map(|actions| actions.iter().cloned().map(Action::from_rpc).collect())
+                    .unwrap_or_default();
+                Receipt {
+                    receipt_id: receipt.receipt_id,
+                    predecessor_id: receipt.predecessor_id,
+                    receiver_id: receipt.receiver_id,
+                    actions,
+                }
+            })
+            .collect();
+        Ok(TransactionStatus {
+            hash: view.transaction.hash,
+            signer_id: view.transaction.signer_id,
+            receiver_id: view.transaction.receiver_id,
+            actions: view
+                .transaction
+                .actions
+                .into_iter()
+                .map(Action::from_rpc)
+                .collect(),
+            status: ExecutionStatus::from_rpc(&view.status),
+            transaction_outcome: view.transaction_outcome.try_into()?,
+            receipts_outcome: view
+                .receipts_outcome
+                .into_iter()
+                .map(ExecutionOutcome::try_from)
+                .collect::<Result<_, _>>()?,
+            receipts,
+        })
+    }
+}
+
+/// Default number of concurrent requests for bulk methods.
+const DEFAULT_CONCURRENCY: usize = 16;
+
+/// Code hash reported for accounts without a deployed contract.
+const EMPTY_CODE_HASH: &str = "11111111111111111111111111111111";
+
+/// Full state of a NEAR account at a given block.
+///
+/// All amounts are in yoctoNEAR. NEAR requires every account to keep enough
+/// balance to pay for its storage; tokens locked for staking count towards
+/// that requirement, so only the remainder is reserved from `amount`.
+#[derive(Debug, Clone)]
+pub struct AccountSnapshot {
+    /// Liquid balance (the `amount` field of `view_account`).
+    pub amount: u128,
+    /// Balance locked for staking (validators).
+    pub locked: u128,
+    /// Storage used by the account in bytes.
+    pub storage_usage: u64,
+    /// Hash of the deployed contract code.
+    pub code_hash: String,
+    /// Height of the block the snapshot was taken at.
+    pub block_height: u64,
+    /// Hash of the block the snapshot was taken at.
+    pub block_hash: String,
+    /// Storage staking price per byte at the time of the snapshot.
+    pub storage_price_per_byte: u128,
+}
+
+impl AccountSnapshot {
+    /// Total amount required to cover the account's storage.
+    pub fn storage_cost(&self) -> u128 {
+        u128::from(self.storage_usage).saturating_mul(self.storage_price_per_byte)
+    }
+
+    /// Part of the liquid balance reserved for storage staking.
+    ///
+    /// Locked (staked) tokens are counted first, so a validator with enough
+    /// stake has nothing reserved from its liquid balance.
+    pub fn storage_reserved(&self) -> u128 {
+        self.storage_cost().saturating_sub(self.locked)
+    }
+
+    /// Balance that can actually be spent or transferred.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::near::AccountSnapshot;
+    /// let snapshot = AccountSnapshot {
+    ///     amount: 1_000_000_000_000_000_000_000_000,
+    ///     locked: 0,
+    ///     storage_usage: 182,
+    ///     code_hash: "11111111111111111111111111111111".to_string(),
+    ///     block_height: 1,
+    ///     block_hash: String::new(),
+    ///     storage_price_per_byte: 10_000_000_000_000_000_000,
+    /// };
+    /// assert_eq!(snapshot.available(), 998_180_000_000_000_000_000_000);
+    /// ```
+    pub fn available(&self) -> u128 {
+        self.amount.saturating_sub(self.storage_reserved())
+    }
+
+    /// Returns `true` if a contract is deployed on the account.
+    pub fn has_contract(&self) -> bool {
+        self.code_hash != EMPTY_CODE_HASH
+    }
 }
 
 /// Aggregated transaction actions data.
@@ -57,7 +938,7 @@ struct AccountView {
 #[derive(Deserialize, Debug, Clone)]
 pub struct ActionsAgg {
     /// Total deposit amount in the transaction.
-    pub deposit: f64,
+    pub deposit: NearAmount,
 }
 
 /// NEAR blockchain transaction information.
@@ -78,6 +959,127 @@ pub struct Transaction {
     pub block_timestamp: String,
     /// Aggregated actions data (deposits, etc.).
     pub actions_agg: ActionsAgg,
+    /// Decoded actions of the transaction.
+    #[serde(default)]
+    pub actions: Vec<TransactionAction>,
+    /// NearBlocks row ID, which doubles as the pagination cursor.
+    #[serde(default, deserialize_with = "deserialize_row_id")]
+    id: Option<String>,
+}
+
+impl Transaction {
+    /// Block timestamp in nanoseconds, or `None` if the indexer sent an invalid value.
+    pub fn timestamp_ns(&self) -> Option<u64> {
+        self.block_timestamp.parse().ok()
+    }
+
+    /// Returns `true` if an action is a NEP-141 `ft_transfer` or `ft_transfer_call`,
+    /// in which case the receiver is a token contract.
+    pub fn has_token_transfer(&self) -> bool {
+        self.actions
+            .iter()
+            .any(TransactionAction::is_token_transfer)
+    }
+}
+
+/// Kind of a transaction action, as reported by NearBlocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionKind {
+    /// Creates the receiver account.
+    CreateAccount,
+    /// Deploys a contract to the receiver account.
+    DeployContract,
+    /// Calls a contract method.
+    FunctionCall,
+    /// Transfers NEAR to the receiver.
+    Transfer,
+    /// Stakes NEAR as a validator.
+    Stake,
+    /// Adds an access key.
+    AddKey,
+    /// Deletes an access key.
+    DeleteKey,
+    /// Deletes the receiver account.
+    DeleteAccount,
+    /// Meta transaction relayed on behalf of another account.
+    Delegate,
+    /// Any other kind, by its NearBlocks name.
+    Other(String),
+}
+
+impl ActionKind {
+    /// Parses a NearBlocks action name such as `FUNCTION_CALL`.
+    fn from_indexer(name: &str) -> Self {
+        match name {
+            "CREATE_ACCOUNT" => ActionKind::CreateAccount,
+            "DEPLOY_CONTRACT" => ActionKind::DeployContract,
+            "FUNCTION_CALL" => ActionKind::FunctionCall,
+            "TRANSFER" => ActionKind::Transfer,
+            "STAKE" => ActionKind::Stake,
+            "ADD_KEY" => ActionKind::AddKey,
+            "DELETE_KEY" => ActionKind::DeleteKey,
+            "DELETE_ACCOUNT" => ActionKind::DeleteAccount,
+            "DELEGATE" | "DELEGATE_ACTION" => ActionKind::Delegate,
+            other => ActionKind::Other(other.to_string()),
+        }
+    }
+}
+
+/// One action of a transaction fetched from NearBlocks.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "IndexerActionView")]
+pub struct TransactionAction {
+    /// Action kind.
+    pub kind: ActionKind,
+    /// Called method, for function calls.
+    pub method_name: Option<String>,
+    /// Call arguments decoded as JSON (or the raw text when they aren't JSON),
+    /// `Null` if absent.
+    pub args: serde_json::Value,
+    /// Attached deposit.
+    pub deposit: NearAmount,
+}
+
+impl TransactionAction {
+    /// Returns `true` for NEP-141 `ft_transfer` and `ft_transfer_call` calls.
+    pub fn is_token_transfer(&self) -> bool {
+        self.kind == ActionKind::FunctionCall
+            && matches!(
+                self.method_name.as_deref(),
+                Some("ft_transfer" | "ft_transfer_call")
+            )
+    }
+}
+
+/// Raw NearBlocks action, e.g.
+/// `{"action": "FUNCTION_CALL", "method": "ft_transfer", "deposit": 1, "args": "{...}"}`.
+/// `args` may be a JSON-encoded string or an object, and `deposit` a number or a string.
+#[derive(Deserialize)]
+struct IndexerActionView {
+    action: String,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    args: serde_json::Value,
+    #[serde(default)]
+    deposit: NearAmount,
+}
+
+impl From<IndexerActionView> for TransactionAction {
+    fn from(view: IndexerActionView) -> Self {
+        let args = match view.args {
+            serde_json::Value::String(text) => {
+                serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text))
+            }
+            args => args,
+        };
+        TransactionAction {
+            kind: ActionKind::from_indexer(&view.action),
+            method_name: view.method.filter(|method| !method.is_empty()),
+            args,
+            deposit: view.deposit,
+        }
+    }
 }
 
 /// Response structure from NearBlocks API transaction endpoint.
@@ -85,33 +1087,143 @@ pub struct Transaction {
 struct NearBlocksResponse {
     /// List of transactions.
     txns: Vec<Transaction>,
+    /// Cursor of the next page; absent on the last page.
+    #[serde(default, deserialize_with = "deserialize_row_id")]
+    cursor: Option<String>,
+}
+
+/// Number of rows requested per NearBlocks page.
+const TRANSACTION_PAGE_SIZE: usize = 25;
+
+/// Maximum number of NearBlocks pages read by a single
+/// [`NearClient::fetch_transactions`] call, so that selective filters on a
+/// busy account cannot issue an unbounded number of requests.
+pub const MAX_TRANSACTION_PAGES: usize = 20;
+
+/// Direction of a transaction relative to the queried account.
+///
+/// Alert rules reuse it for the direction of a balance change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Sent to the account by another account
+    In,
+    /// Sent by the account
+    Out,
+}
+
+/// Filters applied while paging through an account's transactions.
+///
+/// The default filter matches every transaction.
+#[derive(Clone, Debug, Default)]
+pub struct TransactionFilter {
+    /// Skip transactions before this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Skip transactions after this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Only keep incoming or outgoing transactions.
+    pub direction: Option<Direction>,
+    /// Only keep transactions sent by or to this account.
+    pub counterparty: Option<String>,
+    /// Only keep transactions depositing at least this amount.
+    pub min_amount: Option<NearAmount>,
+}
+
+impl TransactionFilter {
+    /// Returns `true` if `tx` of `account_id` passes every filter except `since`,
+    /// which ends the pagination instead.
+    fn matches(&self, account_id: &str, tx: &Transaction) -> bool {
+        if let Some(until) = self.until {
+            let until_ns = until.timestamp_nanos_opt().unwrap_or(i64::MAX) as u64;
+            if tx.timestamp_ns().is_some_and(|ns| ns > until_ns) {
+                return false;
+            }
+        }
+        let outgoing = tx.signer_id == account_id;
+        match self.direction {
+            Some(Direction::Out) if !outgoing => return false,
+            Some(Direction::In) if outgoing || tx.receiver_id != account_id => return false,
+            _ => {}
+        }
+        if let Some(counterparty) = &self.counterparty {
+            if tx.signer_id != *counterparty && tx.receiver_id != *counterparty {
+                return false;
+            }
+        }
+        match self.min_amount {
+            Some(min_amount) => tx.actions_agg.deposit >= min_amount,
+            None => true,
+        }
+    }
+
+    /// Returns `true` if `tx` is older than `since`.
+    fn is_before_range(&self, tx: &Transaction) -> bool {
+        match (self.since, tx.timestamp_ns()) {
+            (Some(since), Some(ns)) => ns < since.timestamp_nanos_opt().unwrap_or(0).max(0) as u64,
+            _ => false,
+        }
+    }
+}
+
+/// One page of an account's transactions.
+#[derive(Debug, Clone)]
+pub struct TransactionPage {
+    /// Matching transactions, newest first.
+    pub transactions: Vec<Transaction>,
+    /// Cursor to pass to [`NearClient::fetch_transactions`] for the next
+    /// page; `None` once the history (or the `since` bound) is exhausted.
+    pub cursor: Option<String>,
+    /// `true` if [`MAX_TRANSACTION_PAGES`] pages were read before `limit`
+    /// transactions were found. The indexer pages from the newest
+    /// transaction and `until` is applied while paging, so an old time range
+    /// may need several calls; continue with `cursor`.
+    pub page_limit_reached: bool,
 }
 
 /// Client for interacting with the NEAR Protocol RPC and NearBlocks API.
 ///
 /// This client provides methods to:
-/// - Fetch account balances from NEAR RPC
+/// - Fetch account balances from NEAR RPC, failing over between endpoints
 /// - Fetch transaction history from NearBlocks API
 ///
+/// Transient failures are retried with exponential backoff (see [`RetryPolicy`]).
+///
 /// # Examples
 ///
 /// ```no_run
-/// # use near_balance_monitor::near::NearClient;
+/// # use near_balance_monitor::near::{NearClient, TransactionFilter};
+/// # use near_balance_monitor::network::NetworkConfig;
 /// # #[tokio::main]
-/// # async fn main() -> Result<(), String> {
-/// let client = NearClient::new();
+/// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+/// let client = NearClient::new(NetworkConfig::testnet());
 /// let balance = client.fetch_balance("example.near").await?;
-/// let transactions = client.fetch_transactions("example.near").await?;
+/// let transactions = client
+///     .fetch_transactions("example.near", &TransactionFilter::default(), 10, None)
+///     .await?;
 /// # Ok(())
 /// # }
 /// ```
 pub struct NearClient {
     /// Internal HTTP client for making requests.
     client: reqwest::Client,
+    /// Network profile with the RPC and indexer endpoints.
+    config: NetworkConfig,
+    /// RPC endpoints with health statistics used for failover.
+    endpoints: EndpointPool,
+    /// Archival RPC endpoints, used for queries at past blocks.
+    archival: EndpointPool,
+    /// Retry policy applied to RPC and indexer requests.
+    retry: RetryPolicy,
+    /// Storage staking price per byte, fetched from the protocol config.
+    storage_price: Mutex<Option<CachedStoragePrice>>,
+    /// Maximum number of concurrent requests issued by bulk methods.
+    concurrency: usize,
+    /// Fungible token metadata by contract ID; metadata is effectively immutable.
+    ft_metadata_cache: Mutex<HashMap<String, FtMetadata>>,
 }
 
 impl NearClient {
-    /// Creates a new `NearClient` instance.
+    /// Creates a new `NearClient` instance for the given network profile.
     ///
     /// Initializes a default `reqwest` HTTP client for making RPC requests.
     ///
@@ -119,113 +1231,273 @@ impl NearClient {
     ///
     /// ```
     /// use near_balance_monitor::near::NearClient;
+    /// use near_balance_monitor::network::NetworkConfig;
     ///
-    /// let client = NearClient::new();
+    /// let client = NearClient::new(NetworkConfig::mainnet());
     /// ```
-    pub fn new() -> Self {
+    pub fn new(config: NetworkConfig) -> Self {
+        log::info!(
+            "NEAR client configured network={} rpc={:?} archival={:?} indexer={}",
+            config.network,
+            config.rpc_urls,
+            config.archival_rpc_urls,
+            config.indexer_url
+        );
         Self {
             client: reqwest::Client::new(),
+            endpoints: EndpointPool::new(config.rpc_urls.clone()),
+            archival: EndpointPool::new(config.archival_rpc_urls.clone()),
+            config,
+            retry: RetryPolicy::default(),
+            storage_price: Mutex::new(None),
+            concurrency: DEFAULT_CONCURRENCY,
+            ft_metadata_cache: Mutex::new(HashMap::new()),
         }
     }
 
-    /// Fetches the last 10 unique transactions for a NEAR account.
+    /// Sets the maximum number of concurrent requests used by bulk methods
+    /// such as [`fetch_balances`](Self::fetch_balances). Clamped to at least 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Replaces the retry policy used for RPC and indexer requests.
     ///
-    /// Queries the NearBlocks API for transaction history, deduplicates by hash,
-    /// sorts by timestamp (descending), and returns up to 10 transactions.
+    /// # Examples
     ///
-    /// # Arguments
+    /// ```
+    /// use near_balance_monitor::near::NearClient;
+    /// use near_balance_monitor::network::NetworkConfig;
+    /// use near_balance_monitor::retry::RetryPolicy;
     ///
-    /// * `account_id` - The NEAR account ID (e.g., "example.near")
+    /// let client = NearClient::new(NetworkConfig::mainnet())
+    ///     .with_retry_policy(RetryPolicy::default());
+    /// ```
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        log::debug!("NEAR client retry policy {:?}", retry);
+        self.retry = retry;
+        self
+    }
+
+    /// Fetches a page of unique transactions for a NEAR account, newest first.
     ///
-    /// # Returns
+    /// Walks the NearBlocks API with its cursor, deduplicates rows by hash and
+    /// applies `filter` until `limit` transactions were collected, the history
+    /// is exhausted, the `since` bound is passed, or [`MAX_TRANSACTION_PAGES`]
+    /// pages were read (see [`TransactionPage::page_limit_reached`]). Pass the
+    /// returned cursor back as `cursor` to continue.
+    ///
+    /// # Arguments
     ///
-    /// Returns `Ok(Vec<Transaction>)` with up to 10 transactions, or an error message.
+    /// * `account_id` - The NEAR account ID (e.g., "example.near")
+    /// * `filter` - Time range, direction, counterparty and amount filters
+    /// * `limit` - Maximum number of transactions to return
+    /// * `cursor` - Cursor of a previous [`TransactionPage`], or `None` to start
+    ///   from the newest transaction
     ///
     /// # Errors
     ///
-    /// Returns `Err(String)` if:
-    /// - The HTTP request fails
-    /// - The response cannot be parsed
+    /// Returns an [`Error`] if:
+    /// - The HTTP request fails after all retries (`Transport`, `HttpStatus`)
+    /// - The response cannot be parsed (`Indexer`)
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::near::{NearClient, TransactionFilter};
+    /// # use near_balance_monitor::network::NetworkConfig;
     /// # #[tokio::main]
-    /// # async fn main() -> Result<(), String> {
-    /// let client = NearClient::new();
-    /// let transactions = client.fetch_transactions("example.near").await?;
-    /// for tx in transactions {
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// let filter = TransactionFilter::default();
+    /// let page = client.fetch_transactions("example.near", &filter, 10, None).await?;
+    /// for tx in &page.transactions {
     ///     println!("Transaction: {}", tx.hash);
     /// }
+    /// if let Some(cursor) = page.cursor.as_deref() {
+    ///     let next = client.fetch_transactions("example.near", &filter, 10, Some(cursor)).await?;
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn fetch_transactions(&self, account_id: &str) -> Result<Vec<Transaction>, String> {
-        log::debug!("Fetching transactions account={} limit=25", account_id);
-        let url = format!(
-            "https://api.nearblocks.io/v1/account/{}/txns?limit=25",
-            account_id
-        );
-
-        let start = Instant::now();
-        let response = self.client.get(&url).send().await.map_err(|e| {
-            log::error!(
-                "NearBlocks API request failed account={}: {}",
-                account_id,
-                e
-            );
-            format!("HTTP request failed: {e}")
-        })?;
-
+    pub async fn fetch_transactions(
+        &self,
+        account_id: &str,
+        filter: &TransactionFilter,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<TransactionPage, Error> {
         log::debug!(
-            "NearBlocks API responded account={} duration_ms={} status={:?}",
+            "Fetching transactions account={} limit={} cursor={:?} filter={:?}",
             account_id,
-            start.elapsed().as_millis(),
-            response.status()
+            limit,
+            cursor,
+            filter
         );
+        let mut transactions = Vec::new();
+        let mut seen_hashes = HashSet::new();
+        let mut cursor = cursor.map(str::to_string);
+        let mut page_limit_reached = false;
 
-        let near_blocks_response: NearBlocksResponse = response.json().await.map_err(|e| {
-            log::error!(
-                "Failed to parse NearBlocks response account={}: {}",
+        for page in 1..=MAX_TRANSACTION_PAGES {
+            let url = self.transactions_url(account_id, cursor.as_deref())?;
+            let response: NearBlocksResponse =
+                self.indexer_get(url.as_str()).await.inspect_err(|e| {
+                    log::error!(
+                        "NearBlocks API request failed account={}: {}",
+                        account_id,
+                        e
+                    )
+                })?;
+            log::debug!(
+                "Fetched transaction page account={} page={} rows={}",
                 account_id,
-                e
+                page,
+                response.txns.len()
             );
-            format!("Failed to parse response: {e}")
-        })?;
+            let is_last_page = response.txns.is_empty() || response.cursor.is_none();
 
-        let mut txs = Vec::new();
-        let mut seen_hashes = std::collections::HashSet::new();
+            let mut last_row = None;
+            for tx in response.txns {
+                if transactions.len() >= limit {
+                    // Stopped mid-page: resume right after the last row read.
+                    log::info!(
+                        "Successfully fetched transactions account={} count={}",
+                        account_id,
+                        transactions.len()
+                    );
+                    return Ok(TransactionPage {
+                        transactions,
+                        cursor: last_row.or(response.cursor),
+                        page_limit_reached: false,
+                    });
+                }
+                if filter.is_before_range(&tx) {
+                    log::debug!("Reached start of range account={}", account_id);
+                    return Ok(TransactionPage {
+                        transactions,
+                        cursor: None,
+                        page_limit_reached: false,
+                    });
+                }
+                last_row = tx.id.clone();
+                if seen_hashes.insert(tx.hash.clone()) && filter.matches(account_id, &tx) {
+                    transactions.push(tx);
+                }
+            }
 
-        for tx in near_blocks_response.txns {
-            if seen_hashes.insert(tx.hash.clone()) {
-                txs.push(tx);
+            cursor = response.cursor;
+            if is_last_page || transactions.len() >= limit {
+                break;
             }
+            page_limit_reached = page == MAX_TRANSACTION_PAGES;
         }
 
-        log::debug!(
-            "Deduplicated transactions account={} unique_count={}",
-            account_id,
-            txs.len()
-        );
-
-        // Sort by timestamp descending
-        txs.sort_by(|a, b| b.block_timestamp.cmp(&a.block_timestamp));
-        txs.truncate(10);
-
+        if page_limit_reached {
+            log::warn!(
+                "Stopped paging transactions account={} pages={} count={} limit={}",
+                account_id,
+                MAX_TRANSACTION_PAGES,
+                transactions.len(),
+                limit
+            );
+        }
         log::info!(
             "Successfully fetched transactions account={} count={}",
             account_id,
-            txs.len()
+            transactions.len()
         );
+        Ok(TransactionPage {
+            transactions,
+            cursor,
+            page_limit_reached,
+        })
+    }
 
-        Ok(txs)
+    /// Fetches the status of a transaction with all its receipts and outcomes.
+    ///
+    /// Calls `EXPERIMENTAL_tx_status` on the regular endpoints first; regular
+    /// nodes only keep recent transactions, so an `UNKNOWN_TRANSACTION`
+    /// answer is retried on the archival endpoints.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - Transaction hash
+    /// * `signer_id` - Account that signed the transaction, used to route the
+    ///   query to the right shard
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if:
+    /// - The HTTP request fails (`Transport`, `HttpStatus`)
+    /// - The RPC returns an error, e.g. `UNKNOWN_TRANSACTION` (`Rpc`)
+    /// - The response cannot be parsed (`Parse`)
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// let status = client.fetch_transaction_status("8Hx...", "example.near").await?;
+    /// println!("Gas burnt: {}", status.gas_burnt());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_transaction_status(
+        &self,
+        tx_hash: &str,
+        signer_id: &str,
+    ) -> Result<TransactionStatus, Error> {
+        log::debug!(
+            "Fetching transaction status hash={} signer={}",
+            tx_hash,
+            signer_id
+        );
+        let params = serde_json::json!({
+            "tx_hash": tx_hash,
+            "sender_account_id": signer_id,
+        });
+        let view: TransactionStatusView = match self
+            .rpc_call("EXPERIMENTAL_tx_status", params.clone())
+            .await
+        {
+            Err(e) if e.is_unknown_transaction() => {
+                log::debug!(
+                    "Transaction not on regular endpoints, trying archival hash={}",
+                    tx_hash
+                );
+                self.rpc_call_on(RpcPool::Archival, "EXPERIMENTAL_tx_status", params)
+                    .await
+            }
+            result => result,
+        }
+        .inspect_err(|e| {
+            log::error!(
+                "Transaction status failed hash={} signer={} kind={}: {}",
+                tx_hash,
+                signer_id,
+                e.kind(),
+                e
+            )
+        })?;
+        let status = TransactionStatus::try_from(view)?;
+        log::debug!(
+            "Fetched transaction status hash={} receipts={} failed={}",
+            tx_hash,
+            status.receipts_outcome.len(),
+            status.status.is_failure()
+        );
+        Ok(status)
     }
 
     /// Fetches the current balance of a NEAR account in yoctoNEAR.
     ///
-    /// Queries the NEAR RPC `view_account` method with finality set to "final"
-    /// to get the most recent confirmed balance.
+    /// This is the liquid `amount` of [`fetch_account`](Self::fetch_account);
+    /// use that method to also get locked, storage and available amounts.
     ///
     /// # Arguments
     ///
@@ -234,92 +1506,1183 @@ impl NearClient {
     /// # Returns
     ///
     /// Returns `Ok(u128)` with the balance in yoctoNEAR (1 NEAR = 10^24 yoctoNEAR),
-    /// or an error message.
+    /// or an error.
     ///
     /// # Errors
     ///
-    /// Returns `Err(String)` if:
-    /// - The HTTP request fails
-    /// - The RPC returns an error (e.g., account not found)
-    /// - The response cannot be parsed
-    /// - The balance amount cannot be parsed as u128
+    /// Returns an [`Error`] if:
+    /// - The HTTP request fails (`Transport`, `HttpStatus`)
+    /// - The RPC returns an error, e.g. `UNKNOWN_ACCOUNT` (`Rpc`)
+    /// - The response or the balance amount cannot be parsed (`Parse`)
     ///
     /// # Examples
     ///
     /// ```no_run
     /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
     /// # #[tokio::main]
-    /// # async fn main() -> Result<(), String> {
-    /// let client = NearClient::new();
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
     /// let balance = client.fetch_balance("example.near").await?;
     /// println!("Balance: {} yoctoNEAR", balance);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn fetch_balance(&self, account_id: &str) -> Result<u128, String> {
+    pub async fn fetch_balance(&self, account_id: &str) -> Result<u128, Error> {
+        Ok(self.fetch_account(account_id).await?.amount)
+    }
+
+    /// Fetches the balances of many accounts at once.
+    ///
+    /// Account IDs are deduplicated, then fetched with at most
+    /// `concurrency` requests in flight (see [`with_concurrency`](Self::with_concurrency)).
+    /// Each account gets its own result, so one failing account does not
+    /// affect the others.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = NearClient::new(NetworkConfig::mainnet()).with_concurrency(32);
+    /// let ids = vec!["a.near".to_string(), "b.near".to_string(), "a.near".to_string()];
+    /// let balances = client.fetch_balances(&ids).await;
+    /// assert_eq!(balances.len(), 2);
+    /// # }
+    /// ```
+    pub async fn fetch_balances(
+        &self,
+        account_ids: &[String],
+    ) -> HashMap<String, Result<u128, Error>> {
+        self.fetch_accounts(account_ids)
+            .await
+            .into_iter()
+            .map(|(account_id, result)| (account_id, result.map(|account| account.amount)))
+            .collect()
+    }
+
+    /// Fetches the full state of many accounts at once.
+    ///
+    /// Like [`fetch_balances`](Self::fetch_balances), account IDs are
+    /// deduplicated and fetched with bounded concurrency, each with its own
+    /// result.
+    pub async fn fetch_accounts(
+        &self,
+        account_ids: &[String],
+    ) -> HashMap<String, Result<AccountSnapshot, Error>> {
+        let unique: HashSet<String> = account_ids.iter().cloned().collect();
+        log::debug!(
+            "Fetching accounts requested={} unique={} concurrency={}",
+            account_ids.len(),
+            unique.len(),
+            self.concurrency
+        );
+
+        let start = Instant::now();
+        let results: HashMap<String, Result<AccountSnapshot, Error>> = stream::iter(unique)
+            .map(|account_id| async move {
+                let result = self.fetch_account(&account_id).await;
+                (account_id, result)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        log::debug!(
+            "Fetched accounts count={} failed={} duration_ms={}",
+            results.len(),
+            results.values().filter(|r| r.is_err()).count(),
+            start.elapsed().as_millis()
+        );
+
+        results
+    }
+
+    /// Fetches the full state of a NEAR account.
+    ///
+    /// Queries the NEAR RPC `view_account` method with finality set to "final"
+    /// and combines it with the current storage price so that the returned
+    /// [`AccountSnapshot`] can compute storage-reserved and available amounts.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`fetch_balance`](Self::fetch_balance).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// let account = client.fetch_account("example.near").await?;
+    /// println!("Available: {} yoctoNEAR", account.available());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_account(&self, account_id: &str) -> Result<AccountSnapshot, Error> {
+        self.fetch_account_at(account_id, &BlockReference::Final)
+            .await
+    }
+
+    /// Fetches the full account state at a given block.
+    ///
+    /// Anything but [`BlockReference::Final`] is read from the archival
+    /// endpoints; timestamps are first resolved with
+    /// [`fetch_block`](Self::fetch_block). The storage price is the current one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::{BlockReference, NearClient};
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// let account = client
+    ///     .fetch_account_at("example.near", &BlockReference::Height(120_000_000))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_account_at(
+        &self,
+        account_id: &str,
+        block: &BlockReference,
+    ) -> Result<AccountSnapshot, Error> {
+        log::debug!("Fetching account account={} block={:?}", account_id, block);
+
+        let mut params = serde_json::json!({
+            "request_type": "view_account",
+            "account_id": account_id,
+        });
+        let pool = match block {
+            BlockReference::Final => {
+                params["finality"] = "final".into();
+                RpcPool::Regular
+            }
+            BlockReference::Height(height) => {
+                params["block_id"] = (*height).into();
+                RpcPool::Archival
+            }
+            BlockReference::Hash(hash) => {
+                params["block_id"] = hash.as_str().into();
+                RpcPool::Archival
+            }
+            BlockReference::Timestamp(_) => {
+                let header = self.fetch_block(block).await?;
+                params["block_id"] = header.height.into();
+                RpcPool::Archival
+            }
+        };
+
+        let view: AccountView = self
+            .rpc_call_on(pool, "query", params)
+            .await
+            .inspect_err(|e| {
+                log::error!("RPC error account={} kind={}: {}", account_id, e.kind(), e)
+            })?;
+
+        let snapshot = AccountSnapshot {
+            amount: parse_yocto(&view.amount, account_id)?,
+            locked: parse_yocto(&view.locked, account_id)?,
+            storage_usage: view.storage_usage,
+            code_hash: view.code_hash,
+            block_height: view.block_height,
+            block_hash: view.block_hash,
+            storage_price_per_byte: self.storage_price().await,
+        };
+
         log::debug!(
-            "Fetching balance account={} endpoint={}",
+            "Successfully fetched account account={} balance_yocto={} locked_yocto={} storage_bytes={} block={}",
             account_id,
-            NEAR_RPC_URL
+            snapshot.amount,
+            snapshot.locked,
+            snapshot.storage_usage,
+            snapshot.block_height
         );
 
+        Ok(snapshot)
+    }
+
+    /// Samples an account's balance at the given points in time.
+    ///
+    /// Each time is resolved to a block on the archival endpoints (see
+    /// [`fetch_block`](Self::fetch_block)) and the account is read at that
+    /// block. Up to `concurrency` samples are fetched at once; results keep
+    /// the order of `times`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`Error`] of any sample, except `UNKNOWN_ACCOUNT`,
+    /// which yields a sample without an account.
+    pub async fn fetch_balance_history(
+        &self,
+        account_id: &str,
+        times: &[DateTime<Utc>],
+    ) -> Result<Vec<BalanceSample>, Error> {
+        log::debug!(
+            "Fetching balance history account={} samples={}",
+            account_id,
+            times.len()
+        );
+        stream::iter(times.iter().copied())
+            .map(|at| async move {
+                let block = self.fetch_block(&BlockReference::Timestamp(at)).await?;
+                let account = match self
+                    .fetch_account_at(account_id, &BlockReference::Height(block.height))
+                    .await
+                {
+                    Ok(account) => Some(account),
+                    Err(e) if e.is_account_not_found() => None,
+                    Err(e) => return Err(e),
+                };
+                Ok(BalanceSample { at, block, account })
+            })
+            .buffered(self.concurrency)
+            .try_collect()
+            .await
+    }
+
+    /// Fetches the header of a block.
+    ///
+    /// Heights and hashes are looked up on the archival endpoints. A
+    /// timestamp resolves to the last block produced at or before it: the
+    /// heights between genesis and the final block are binary searched,
+    /// guided by interpolation on block timestamps, with `block` RPC calls.
+    /// Times after the final block resolve to the final block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if a request fails, the block does not exist
+    /// (`UNKNOWN_BLOCK`), or the time is before genesis (`Config`).
+    pub async fn fetch_block(&self, block: &BlockReference) -> Result<BlockHeader, Error> {
+        match block {
+            BlockReference::Final => self.final_block().await,
+            BlockReference::Height(height) => self.block_at_height(*height).await,
+            BlockReference::Hash(hash) => {
+                let view: BlockView = self
+                    .rpc_call_on(
+                        RpcPool::Archival,
+                        "block",
+                        serde_json::json!({ "block_id": hash }),
+                    )
+                    .await?;
+                Ok(view.header)
+            }
+            BlockReference::Timestamp(at) => self.block_at_time(*at).await,
+        }
+    }
+
+    /// Fetches the header of the latest final block.
+    async fn final_block(&self) -> Result<BlockHeader, Error> {
+        let view: BlockView = self
+            .rpc_call("block", serde_json::json!({ "finality": "final" }))
+            .await?;
+        Ok(view.header)
+    }
+
+    /// Fetches a block header by height from the archival endpoints.
+    async fn block_at_height(&self, height: u64) -> Result<BlockHeader, Error> {
+        let view: BlockView = self
+            .rpc_call_on(
+                RpcPool::Archival,
+                "block",
+                serde_json::json!({ "block_id": height }),
+            )
+            .await?;
+        Ok(view.header)
+    }
+
+    /// Returns the first block at a height in `from..until`, skipping heights
+    /// at which no block was produced. `None` if there is none within
+    /// [`MAX_SKIPPED_BLOCKS`] heights.
+    async fn first_block_from(&self, from: u64, until: u64) -> Result<Option<BlockHeader>, Error> {
+        for height in (from..until).take(MAX_SKIPPED_BLOCKS as usize) {
+            match self.block_at_height(height).await {
+                Ok(header) => return Ok(Some(header)),
+                Err(e) if e.is_unknown_block() => {
+                    log::debug!("No block at height={}, trying next", height)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Resolves a point in time to the last block produced at or before it.
+    async fn block_at_time(&self, at: DateTime<Utc>) -> Result<BlockHeader, Error> {
+        let target = at
+            .timestamp_nanos_opt()
+            .and_then(|ns| u64::try_from(ns).ok())
+            .ok_or_else(|| Error::Config(format!("time {at} is out of range")))?;
+
+        let mut high = self.final_block().await?;
+        if high.timestamp <= target {
+            return Ok(high);
+        }
+
+        let genesis: GenesisConfigView = self
+            .rpc_call_on(
+                RpcPool::Archival,
+                "EXPERIMENTAL_genesis_config",
+                serde_json::Value::Null,
+            )
+            .await?;
+        let mut low = self
+            .first_block_from(genesis.genesis_height, high.height)
+            .await?
+            .ok_or_else(|| Error::Parse("no block found after genesis".to_string()))?;
+        if low.timestamp > target {
+            return Err(Error::Config(format!(
+                "{at} is before the first block of this network"
+            )));
+        }
+
+        // Invariant: low.timestamp <= target < high.timestamp.
+        let mut interpolate = true;
+        let mut probes = 0;
+        while high.height - low.height > 1 {
+            probes += 1;
+            let range = high.height - low.height;
+            let middle = if interpolate {
+                let offset = u128::from(target - low.timestamp) * u128::from(range)
+                    / u128::from(high.timestamp - low.timestamp);
+                (low.height + offset as u64).clamp(low.height + 1, high.height - 1)
+            } else {
+                low.height + range / 2
+            };
+
+            match self.first_block_from(middle, high.height).await? {
+                Some(header) if header.timestamp <= target => low = header,
+                Some(header) => high = header,
+                // Every height up to `high` was skipped within the probe window.
+                None => high.height = middle,
+            }
+            // Fall back to bisection whenever interpolation did not halve the range.
+            interpolate = high.height - low.height <= range / 2;
+        }
+
+        log::debug!(
+            "Resolved time={} block={} block_timestamp={} probes={}",
+            at,
+            low.height,
+            low.timestamp,
+            probes
+        );
+        Ok(low)
+    }
+
+    /// Returns the storage staking price per byte.
+    ///
+    /// The price is read from `EXPERIMENTAL_protocol_config` on first use and
+    /// cached for the lifetime of the client. If the call fails, the
+    /// long-standing mainnet value of 10^19 yoctoNEAR per byte is used: for
+    /// good if the node rejected the method (e.g. it does not support it),
+    /// otherwise for [`STORAGE_PRICE_RETRY_INTERVAL`] before trying again.
+    ///
+    /// The price is optional for callers, so the call is made once against
+    /// the healthiest endpoint, without retries and without affecting the
+    /// endpoint health statistics.
+    async fn storage_price(&self) -> u128 {
+        let cached = *self.storage_price.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cached {
+            if cached.refresh_at.is_none_or(|at| Instant::now() < at) {
+                return cached.price;
+            }
+        }
+
+        let (price, refresh_at) = match self.fetch_storage_price().await {
+            Ok(price) => {
+                log::debug!("Fetched storage price yocto_per_byte={}", price);
+                (price, None)
+            }
+            Err(AttemptError::Request(e) | AttemptError::Endpoint(e @ Error::Rpc { .. })) => {
+                log::warn!(
+                    "Storage price unavailable, using default yocto_per_byte={}: {}",
+                    DEFAULT_STORAGE_PRICE_PER_BYTE,
+                    e
+                );
+                (DEFAULT_STORAGE_PRICE_PER_BYTE, None)
+            }
+            Err(AttemptError::Endpoint(e) | AttemptError::Transient(e)) => {
+                log::warn!(
+                    "Failed to fetch storage price, using default yocto_per_byte={} retry_in_secs={}: {}",
+                    DEFAULT_STORAGE_PRICE_PER_BYTE,
+                    STORAGE_PRICE_RETRY_INTERVAL.as_secs(),
+                    e
+                );
+                (
+                    DEFAULT_STORAGE_PRICE_PER_BYTE,
+                    Some(Instant::now() + STORAGE_PRICE_RETRY_INTERVAL),
+                )
+            }
+        };
+        *self.storage_price.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(CachedStoragePrice { price, refresh_at });
+        price
+    }
+
+    /// Makes a single `EXPERIMENTAL_protocol_config` request to the
+    /// healthiest RPC endpoint and returns the storage price.
+    async fn fetch_storage_price(&self) -> Result<u128, AttemptError> {
+        let index = self
+            .endpoints
+            .candidates()
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                AttemptError::Endpoint(Error::Config("No RPC endpoints configured".to_string()))
+            })?;
         let request = RpcRequest {
             jsonrpc: "2.0",
             id: "1",
-            method: "query",
-            params: serde_json::json!({
-                "request_type": "view_account",
-                "finality": "final",
-                "account_id": account_id,
-            }),
+            method: "EXPERIMENTAL_protocol_config",
+            params: serde_json::json!({ "finality": "final" }),
         };
+        let config: ProtocolConfigView = self
+            .rpc_attempt(RpcPool::Regular, self.endpoints.url(index), &request)
+            .await?;
+        config
+            .runtime_config
+            .storage_amount_per_byte
+            .parse::<u128>()
+            .map_err(|e| AttemptError::Request(Error::Parse(format!("invalid storage price: {e}"))))
+    }
 
-        let start = Instant::now();
-        let response = self
-            .client
-            .post(NEAR_RPC_URL)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {e}"))?;
+    /// Calls a view method on a contract and returns the raw result bytes.
+    ///
+    /// Arguments are serialized as JSON and sent base64-encoded, as required
+    /// by the `call_function` query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the request fails. If the contract panics or
+    /// does not have the method, the node puts the message in an `error`
+    /// field of the result, which is returned as an [`Error::Rpc`] with cause
+    /// `CONTRACT_EXECUTION_ERROR`.
+    pub async fn call_function(
+        &self,
+        contract_id: &str,
+        method_name: &str,
+        args: &serde_json::Value,
+    ) -> Result<Vec<u8>, Error> {
+        log::debug!(
+            "Calling view function contract={} method={}",
+            contract_id,
+            method_name
+        );
+        let view: CallFunctionView = self
+            .rpc_call(
+                "query",
+                serde_json::json!({
+                    "request_type": "call_function",
+                    "finality": "final",
+                    "account_id": contract_id,
+                    "method_name": method_name,
+                    "args_base64": BASE64.encode(args.to_string()),
+                }),
+            )
+            .await?;
+        Ok(view.result)
+    }
+
+    /// Calls a view method and parses its JSON result.
+    pub async fn view_function<T: DeserializeOwned>(
+        &self,
+        contract_id: &str,
+        method_name: &str,
+        args: &serde_json::Value,
+    ) -> Result<T, Error> {
+        let bytes = self.call_function(contract_id, method_name, args).await?;
+        serde_json::from_slice(&bytes).map_err(|e| {
+            Error::Parse(format!(
+                "invalid {method_name} result from {contract_id}: {e}"
+            ))
+        })
+    }
 
+    /// Fetches the NEP-141 token balance of an account, in the token's
+    /// smallest unit.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// let raw = client.ft_balance_of("usdt.tether-token.near", "example.near").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ft_balance_of(&self, contract_id: &str, account_id: &str) -> Result<u128, Error> {
+        let balance: String = self
+            .view_function(
+                contract_id,
+                "ft_balance_of",
+                &serde_json::json!({ "account_id": account_id }),
+            )
+            .await
+            .inspect_err(|e| {
+                log::error!(
+                    "ft_balance_of failed contract={} account={} kind={}: {}",
+                    contract_id,
+                    account_id,
+                    e.kind(),
+                    e
+                )
+            })?;
+        let balance = balance
+            .parse::<u128>()
+            .map_err(|e| Error::Parse(format!("invalid token balance {balance}: {e}")))?;
         log::debug!(
-            "RPC request completed account={} duration_ms={} status={:?}",
+            "Fetched token balance contract={} account={} balance={}",
+            contract_id,
             account_id,
-            start.elapsed().as_millis(),
-            response.status()
+            balance
         );
+        Ok(balance)
+    }
 
-        let rpc_response: RpcResponse = response.json().await.map_err(|e| {
-            log::error!("Failed to parse RPC response account={}: {}", account_id, e);
-            format!("Failed to parse response: {e}")
-        })?;
+    /// Fetches NEP-148 metadata (symbol, decimals) of a fungible token.
+    ///
+    /// Results are cached per contract for the lifetime of the client.
+    pub async fn ft_metadata(&self, contract_id: &str) -> Result<FtMetadata, Error> {
+        if let Some(metadata) = self
+            .ft_metadata_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(contract_id)
+        {
+            return Ok(metadata.clone());
+        }
 
-        if let Some(error) = rpc_response.error {
-            log::error!("RPC error account={}: {:?}", account_id, error);
-            return Err(format!("RPC error: {error}"));
+        let metadata: FtMetadata = self
+            .view_function(contract_id, "ft_metadata", &serde_json::json!({}))
+            .await?;
+        log::debug!(
+            "Fetched token metadata contract={} symbol={} decimals={}",
+            contract_id,
+            metadata.symbol,
+            metadata.decimals
+        );
+        self.ft_metadata_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(contract_id.to_string(), metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Returns the metadata of the token a transaction transfers, if it calls
+    /// `ft_transfer` or `ft_transfer_call` on its receiver.
+    ///
+    /// Failures are logged and yield `None`, so callers can fall back to
+    /// showing raw amounts.
+    pub async fn transaction_token(&self, tx: &Transaction) -> Option<FtMetadata> {
+        if !tx.has_token_transfer() {
+            return None;
         }
+        self.ft_metadata(&tx.receiver_id)
+            .await
+            .inspect_err(|e| {
+                log::warn!(
+                    "Token metadata unavailable contract={} kind={}: {}",
+                    tx.receiver_id,
+                    e.kind(),
+                    e
+                )
+            })
+            .ok()
+    }
 
-        let result = rpc_response.result.ok_or_else(|| {
-            log::error!("No result in RPC response account={}", account_id);
-            "No result in response"
-        })?;
+    /// Fetches many `(contract_id, account_id)` token balances at once.
+    ///
+    /// Pairs are deduplicated and fetched with the same bounded concurrency
+    /// as [`fetch_balances`](Self::fetch_balances).
+    pub async fn fetch_token_balances(
+        &self,
+        pairs: &[(String, String)],
+    ) -> HashMap<(String, String), Result<u128, Error>> {
+        let unique: HashSet<(String, String)> = pairs.iter().cloned().collect();
+        log::debug!(
+            "Fetching token balances requested={} unique={}",
+            pairs.len(),
+            unique.len()
+        );
+        stream::iter(unique)
+            .map(|pair| async move {
+                let result = self.ft_balance_of(&pair.0, &pair.1).await;
+                (pair, result)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
 
-        let balance = result.amount.parse::<u128>().map_err(|e| {
+    /// Calls a staking-pool view method that returns a `U128` string amount.
+    async fn pool_amount(
+        &self,
+        pool_id: &str,
+        method_name: &str,
+        account_id: &str,
+    ) -> Result<u128, Error> {
+        let amount: String = self
+            .view_function(
+                pool_id,
+                method_name,
+                &serde_json::json!({ "account_id": account_id }),
+            )
+            .await?;
+        amount
+            .parse::<u128>()
+            .map_err(|e| Error::Parse(format!("invalid {method_name} amount {amount}: {e}")))
+    }
+
+    /// Fetches the balance staked by an account with a staking pool
+    /// (`get_account_staked_balance`), in yoctoNEAR.
+    pub async fn staked_balance(&self, pool_id: &str, account_id: &str) -> Result<u128, Error> {
+        self.pool_amount(pool_id, "get_account_staked_balance", account_id)
+            .await
+    }
+
+    /// Fetches the unstaked balance of an account in a staking pool
+    /// (`get_account_unstaked_balance`), in yoctoNEAR.
+    pub async fn unstaked_balance(&self, pool_id: &str, account_id: &str) -> Result<u128, Error> {
+        self.pool_amount(pool_id, "get_account_unstaked_balance", account_id)
+            .await
+    }
+
+    /// Returns whether the account's unstaked balance can be withdrawn
+    /// (`is_account_unstaked_balance_available`).
+    pub async fn is_unstaked_balance_available(
+        &self,
+        pool_id: &str,
+        account_id: &str,
+    ) -> Result<bool, Error> {
+        self.view_function(
+            pool_id,
+            "is_account_unstaked_balance_available",
+            &serde_json::json!({ "account_id": account_id }),
+        )
+        .await
+    }
+
+    /// Fetches the full delegation state of an account in a staking pool.
+    ///
+    /// The three view methods are queried concurrently.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// let staking = client.fetch_staking("example.poolv1.near", "example.near").await?;
+    /// println!("Staked: {} yoctoNEAR", staking.staked);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_staking(
+        &self,
+        pool_id: &str,
+        account_id: &str,
+    ) -> Result<StakingBalance, Error> {
+        let (staked, unstaked, unstaked_available) = futures::try_join!(
+            self.staked_balance(pool_id, account_id),
+            self.unstaked_balance(pool_id, account_id),
+            self.is_unstaked_balance_available(pool_id, account_id),
+        )
+        .inspect_err(|e| {
             log::error!(
-                "Failed to parse balance amount account={}: {}",
+                "Staking fetch failed pool={} account={} kind={}: {}",
+                pool_id,
                 account_id,
+                e.kind(),
                 e
-            );
-            format!("Failed to parse amount: {e}")
+            )
         })?;
+        log::debug!(
+            "Fetched staking pool={} account={} staked={} unstaked={} available={}",
+            pool_id,
+            account_id,
+            staked,
+            unstaked,
+            unstaked_available
+        );
+        Ok(StakingBalance {
+            staked,
+            unstaked,
+            unstaked_available,
+        })
+    }
+
+    /// Fetches many `(pool_id, account_id)` delegations at once.
+    ///
+    /// Pairs are deduplicated and fetched with the same bounded concurrency
+    /// as [`fetch_balances`](Self::fetch_balances).
+    pub async fn fetch_staking_balances(
+        &self,
+        pairs: &[(String, String)],
+    ) -> HashMap<(String, String), Result<StakingBalance, Error>> {
+        let unique: HashSet<(String, String)> = pairs.iter().cloned().collect();
+        log::debug!(
+            "Fetching staking balances requested={} unique={}",
+            pairs.len(),
+            unique.len()
+        );
+        stream::iter(unique)
+            .map(|pair| async move {
+                let result = self.fetch_staking(&pair.0, &pair.1).await;
+                (pair, result)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Fetches all access keys of an account (`view_access_key_list`).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use near_balance_monitor::near::NearClient;
+    /// # use near_balance_monitor::network::NetworkConfig;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), near_balance_monitor::error::Error> {
+    /// let client = NearClient::new(NetworkConfig::mainnet());
+    /// for key in client.fetch_access_keys("example.near").await? {
+    ///     println!("{}", key.public_key);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn fetch_access_keys(&self, account_id: &str) -> Result<Vec<AccessKey>, Error> {
+        let view: AccessKeyListView = self
+            .rpc_call(
+                "query",
+                serde_json::json!({
+                    "request_type": "view_access_key_list",
+                    "finality": "final",
+                    "account_id": account_id,
+                }),
+            )
+            .await
+            .inspect_err(|e| {
+                log::error!(
+                    "Access key list failed account={} kind={}: {}",
+                    account_id,
+                    e.kind(),
+                    e
+                )
+            })?;
 
+        let keys = view
+            .keys
+            .into_iter()
+            .map(|key| {
+                Ok(AccessKey {
+                    public_key: key.public_key,
+                    permission: key.access_key.permission.into_permission(account_id)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
         log::debug!(
-            "Successfully fetched balance account={} balance_yocto={}",
+            "Fetched access keys account={} count={}",
             account_id,
-            balance
+            keys.len()
         );
+        Ok(keys)
+    }
 
-        Ok(balance)
+    /// Fetches the access keys of many accounts at once.
+    ///
+    /// Account IDs are deduplicated and fetched with the same bounded
+    /// concurrency as [`fetch_balances`](Self::fetch_balances).
+    pub async fn fetch_access_key_lists(
+        &self,
+        account_ids: &[String],
+    ) -> HashMap<String, Result<Vec<AccessKey>, Error>> {
+        let unique: HashSet<String> = account_ids.iter().cloned().collect();
+        log::debug!(
+            "Fetching access keys requested={} unique={}",
+            account_ids.len(),
+            unique.len()
+        );
+        stream::iter(unique)
+            .map(|account_id| async move {
+                let result = self.fetch_access_keys(&account_id).await;
+                (account_id, result)
+            })
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await
+    }
+
+    /// Calls a lockup-contract view method without arguments that returns a
+    /// `U128` string amount.
+    async fn lockup_amount(&self, account_id: &str, method_name: &str) -> Result<u128, Error> {
+        let amount: String = self
+            .view_function(account_id, method_name, &serde_json::json!({}))
+            .await?;
+        parse_yocto(&amount, account_id)
+    }
+
+    /// Fetches the owner, balances and vesting schedule of a lockup contract.
+    ///
+    /// Use [`is_lockup_account`] to decide whether an account is a lockup.
+    /// The view methods are queried concurrently.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if any view call fails, e.g. because the account
+    /// is not a lockup contract (an [`Error::Rpc`] with cause
+    /// `CONTRACT_EXECUTION_ERROR`, see [`NearClient::call_function`]).
+    pub async fn fetch_lockup(&self, account_id: &str) -> Result<LockupInfo, Error> {
+        let no_args = serde_json::json!({});
+        let (owner, balance, locked, liquid, unvested, vesting, staking_pool) = futures::try_join!(
+            self.view_function::<String>(account_id, "get_owner_account_id", &no_args),
+            self.lockup_amount(account_id, "get_balance"),
+            self.lockup_amount(account_id, "get_locked_amount"),
+            self.lockup_amount(account_id, "get_liquid_owners_balance"),
+            self.lockup_amount(account_id, "get_unvested_amount"),
+            self.view_function::<VestingView>(account_id, "get_vesting_information", &no_args),
+            self.view_function::<Option<String>>(
+                account_id,
+                "get_staking_pool_account_id",
+                &no_args
+            ),
+        )
+        .inspect_err(|e| {
+            log::error!(
+                "Lockup fetch failed account={} kind={}: {}",
+                account_id,
+                e.kind(),
+                e
+            )
+        })?;
+        log::debug!(
+            "Fetched lockup account={} owner={} locked={} liquid={}",
+            account_id,
+            owner,
+            locked,
+            liquid
+        );
+        Ok(LockupInfo {
+            owner,
+            balance,
+            locked,
+            liquid,
+            unvested,
+            vesting: vesting.try_into()?,
+            staking_pool,
+        })
     }
+
+    /// Performs a JSON-RPC call, failing over between the configured endpoints.
+    ///
+    /// Each attempt walks the endpoints in the order given by the
+    /// [`EndpointPool`] health score. Transport failures, 429/5xx responses
+    /// and unparsable bodies are charged to the endpoint and the next one is
+    /// tried immediately. When every endpoint failed, or the node reported a
+    /// transient cause such as `UNKNOWN_BLOCK`, the call is retried after the
+    /// backoff delay of the [`RetryPolicy`]. Other errors reported by the RPC
+    /// handler (e.g. an unknown account) are returned immediately.
+    async fn rpc_call<T: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        params: serde_json::Value,
+    ) -> Result<T, Error> {
+        self.rpc_call_on(RpcPool::Regular, method, params).await
+    }
+
+    /// Performs a JSON-RPC call against the given set of endpoints.
+    ///
+    /// See [`rpc_call`](Self::rpc_call). On the archival endpoints
+    /// `UNKNOWN_BLOCK` is not transient: archival nodes keep every block, so
+    /// a block they don't know was never produced.
+    async fn rpc_call_on<T: DeserializeOwned>(
+        &self,
+        pool: RpcPool,
+        method: &'static str,
+        params: serde_json::Value,
+    ) -> Result<T, Error> {
+        let endpoints = match pool {
+            RpcPool::Regular => &self.endpoints,
+            RpcPool::Archival => &self.archival,
+        };
+        let request = RpcRequest {
+            jsonrpc: "2.0",
+            id: "1",
+            method,
+            params,
+        };
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut last_error = Error::Config("No RPC endpoints configured".to_string());
+
+            for index in endpoints.candidates() {
+                let endpoint = endpoints.url(index);
+                let start = Instant::now();
+                match self.rpc_attempt::<T>(pool, endpoint, &request).await {
+                    Ok(result) => {
+                        let latency = start.elapsed();
+                        endpoints.record_success(index, latency);
+                        log::debug!(
+                            "RPC request served method={} endpoint={} attempt={} duration_ms={}",
+                            method,
+                            endpoint,
+                            attempt,
+                            latency.as_millis()
+                        );
+                        return Ok(result);
+                    }
+                    Err(AttemptError::Request(e)) => {
+                        // The endpoint answered; the request itself is at fault.
+                        endpoints.record_success(index, start.elapsed());
+                        return Err(e);
+                    }
+                    Err(AttemptError::Transient(e)) => {
+                        // The endpoint answered but could not serve the request yet.
+                        endpoints.record_success(index, start.elapsed());
+                        log::debug!(
+                            "RPC transient error method={} endpoint={}: {}",
+                            method,
+                            endpoint,
+                            e
+                        );
+                        last_error = e;
+                        break;
+                    }
+                    Err(AttemptError::Endpoint(e)) => {
+                        endpoints.record_failure(index);
+                        log::warn!(
+                            "RPC endpoint failed, trying next method={} endpoint={}: {}",
+                            method,
+                            endpoint,
+                            e
+                        );
+                        last_error = e;
+                    }
+                }
+            }
+
+            if !self.retry.should_retry(attempt) {
+                return Err(last_error);
+            }
+            let delay = self.retry.delay_for(attempt);
+            log::warn!(
+                "Retrying RPC request method={} attempt={} max_attempts={} delay_ms={}: {}",
+                method,
+                attempt,
+                self.retry.max_attempts,
+                delay.as_millis(),
+                last_error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Sends a single JSON-RPC request to one endpoint.
+    async fn rpc_attempt<T: DeserializeOwned>(
+        &self,
+        pool: RpcPool,
+        endpoint: &str,
+        request: &RpcRequest,
+    ) -> Result<T, AttemptError> {
+        let mut http_request = self.client.post(endpoint).json(request);
+        if let Some(api_key) = self.config.rpc_api_key_for(endpoint) {
+            http_request = http_request.header("x-api-key", api_key);
+        }
+
+        let response = http_request
+            .send()
+            .await
+            .map_err(|e| AttemptError::Endpoint(Error::Transport(e.to_string())))?;
+
+        let status = response.status();
+        let error = Error::HttpStatus {
+            status: status.as_u16(),
+            url: endpoint.to_string(),
+        };
+        if retry::is_retryable_status(status) {
+            return Err(AttemptError::Endpoint(error));
+        }
+
+        let rpc_response = match response.json::<RpcResponse<serde_json::Value>>().await {
+            // Some nodes send JSON-RPC errors with a 4xx status.
+            Ok(rpc_response) if status.is_success() || rpc_response.error.is_some() => rpc_response,
+            Err(e) if status.is_success() => {
+                return Err(AttemptError::Endpoint(Error::Parse(e.to_string())));
+            }
+            // Any other 4xx, e.g. a rejected API key, fails the same way on
+            // every attempt and says nothing about the endpoint's health.
+            _ if status.is_client_error() => return Err(AttemptError::Request(error)),
+            _ => return Err(AttemptError::Endpoint(error)),
+        };
+
+        if let Some(error) = rpc_response.error {
+            let error_value = error;
+            let error = Error::from_rpc_error(&error_value);
+            let is_retryable = retry::is_retryable_rpc_error(&error_value)
+                && !(pool == RpcPool::Archival && error.is_unknown_block());
+            if is_retryable {
+                return Err(AttemptError::Transient(error));
+            }
+            // HANDLER_ERROR means the node processed the request and rejected it.
+            let is_handler_error =
+                matches!(&error, Error::Rpc { name, .. } if name == "HANDLER_ERROR");
+            return Err(if is_handler_error {
+                AttemptError::Request(error)
+            } else {
+                AttemptError::Endpoint(error)
+            });
+        }
+
+        let result = rpc_response.result.ok_or_else(|| {
+            AttemptError::Endpoint(Error::Parse("no result in response".to_string()))
+        })?;
+        // A contract panic in a `call_function` query is not a JSON-RPC error:
+        // nearcore answers `{"result": {"error": "...", "logs": [], ...}}`.
+        if let Some(message) = result.get("error").and_then(|e| e.as_str()) {
+            return Err(AttemptError::Request(Error::Rpc {
+                name: "HANDLER_ERROR".to_string(),
+                cause: "CONTRACT_EXECUTION_ERROR".to_string(),
+                message: message.to_string(),
+            }));
+        }
+        serde_json::from_value(result)
+            .map_err(|e| AttemptError::Endpoint(Error::Parse(e.to_string())))
+    }
+
+    /// Builds the NearBlocks URL of one page of an account's transactions.
+    ///
+    /// The account ID and cursor are escaped, since they come from user
+    /// input and stored callback data.
+    fn transactions_url(
+        &self,
+        account_id: &str,
+        cursor: Option<&str>,
+    ) -> Result<reqwest::Url, Error> {
+        let invalid = || Error::Config(format!("invalid indexer URL {}", self.config.indexer_url));
+        let mut url = reqwest::Url::parse(&self.config.indexer_url).map_err(|_| invalid())?;
+        url.path_segments_mut()
+            .map_err(|()| invalid())?
+            .pop_if_empty()
+            .extend(["v1", "account", account_id, "txns"]);
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("per_page", &TRANSACTION_PAGE_SIZE.to_string())
+                .append_pair("order", "desc");
+            if let Some(cursor) = cursor {
+                query.append_pair("cursor", cursor);
+            }
+        }
+        Ok(url)
+    }
+
+    /// Performs a GET request against the NearBlocks API with retries.
+    ///
+    /// Transport errors and 408/429/5xx responses are retried according to
+    /// the [`RetryPolicy`]. A `Retry-After` header on a 429 response extends
+    /// the delay, up to the policy's `max_delay`.
+    async fn indexer_get<T: DeserializeOwned>(&self, url: &str) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let mut request = self.client.get(url);
+            if let Some(api_key) = &self.config.indexer_api_key {
+                request = request.bearer_auth(api_key);
+            }
+
+            let start = Instant::now();
+            let (error, retry_after) = match request.send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    log::debug!(
+                        "NearBlocks API responded url={} attempt={} duration_ms={} status={:?}",
+                        url,
+                        attempt,
+                        start.elapsed().as_millis(),
+                        status
+                    );
+                    if retry::is_retryable_status(status) {
+                        let retry_after = response
+                            .headers()
+                            .get(reqwest::header::RETRY_AFTER)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|v| v.parse::<u64>().ok())
+                            .map(Duration::from_secs);
+                        let error = Error::HttpStatus {
+                            status: status.as_u16(),
+                            url: url.to_string(),
+                        };
+                        (error, retry_after)
+                    } else if !status.is_success() {
+                        let body = response.text().await.unwrap_or_default();
+                        return Err(Error::Indexer(format!("status {status}: {body}")));
+                    } else {
+                        return response
+                            .json()
+                            .await
+                            .map_err(|e| Error::Indexer(format!("unexpected response: {e}")));
+                    }
+                }
+                Err(e) => (Error::Transport(e.to_string()), None),
+            };
+
+            if !self.retry.should_retry(attempt) {
+                return Err(error);
+            }
+            let delay = retry_after
+                .map(|d| d.max(self.retry.delay_for(attempt)))
+                .unwrap_or_else(|| self.retry.delay_for(attempt))
+                .min(self.retry.max_delay);
+            log::warn!(
+                "Retrying NearBlocks request url={} attempt={} max_attempts={} delay_ms={}: {}",
+                url,
+                attempt,
+                self.retry.max_attempts,
+                delay.as_millis(),
+                error
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Deserializes a NearBlocks row ID or cursor, which may be a string or a number.
+fn deserialize_row_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(id)) => Some(id),
+            Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        },
+    )
+}
+
+/// Parses a yoctoNEAR amount string returned by the RPC.
+fn parse_yocto(value: &str, account_id: &str) -> Result<u128, Error> {
+    value.parse::<u128>().map_err(|e| {
+        log::error!(
+            "Failed to parse balance amount account={} value={}: {}",
+            account_id,
+            value,
+            e
+        );
+        Error::Parse(format!("invalid amount {value}: {e}"))
+    })
 }
@@ -0,0 +1,195 @@
+//! Network profiles for NEAR RPC and indexer endpoints.
+//!
+//! A [`NetworkConfig`] bundles everything `NearClient` needs to know about
+//! the network it talks to: the JSON-RPC endpoints, the archival RPC
+//! endpoints used for historical queries, the NearBlocks indexer base URL
+//! and optional API keys for each. Profiles exist for mainnet and testnet;
+//! a custom profile can point at a private RPC node or a local sandbox.
+
+use clap::ValueEnum;
+
+/// Default mainnet RPC endpoint.
+const MAINNET_RPC_URL: &str = "https://h36uashbwvxlllkjfzzaxgfu-near-rpc.defuse.org";
+
+/// Default mainnet archival RPC endpoint, used for historical queries.
+const MAINNET_ARCHIVAL_RPC_URL: &str = "https://archival-rpc.mainnet.near.org";
+
+/// Default mainnet NearBlocks API base URL.
+const MAINNET_INDEXER_URL: &str = "https://api.nearblocks.io";
+
+/// Default testnet RPC endpoint.
+const TESTNET_RPC_URL: &str = "https://rpc.testnet.near.org";
+
+/// Default testnet archival RPC endpoint, used for historical queries.
+const TESTNET_ARCHIVAL_RPC_URL: &str = "https://archival-rpc.testnet.near.org";
+
+/// Default testnet NearBlocks API base URL.
+const TESTNET_INDEXER_URL: &str = "https://api-testnet.nearblocks.io";
+
+/// Known NEAR networks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Network {
+    /// NEAR mainnet
+    Mainnet,
+    /// NEAR testnet
+    Testnet,
+    /// Custom network (requires an explicit RPC URL)
+    Custom,
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => write!(f, "testnet"),
+            Network::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+/// Endpoints and credentials used by `NearClient`.
+#[derive(Clone, Debug)]
+pub struct NetworkConfig {
+    /// Network this profile belongs to.
+    pub network: Network,
+    /// NEAR JSON-RPC endpoint URLs, in order of preference.
+    pub rpc_urls: Vec<String>,
+    /// Archival RPC endpoint URLs, used for queries at past blocks.
+    pub archival_rpc_urls: Vec<String>,
+    /// NearBlocks API base URL (without trailing slash).
+    pub indexer_url: String,
+    /// Optional RPC provider API key, sent as `x-api-key`.
+    pub rpc_api_key: Option<String>,
+    /// Endpoints the RPC API key is sent to: the RPC and archival URLs given
+    /// explicitly, never the built-in public defaults.
+    pub rpc_api_key_urls: Vec<String>,
+    /// Optional NearBlocks API key, sent to the indexer as a bearer token.
+    pub indexer_api_key: Option<String>,
+}
+
+impl NetworkConfig {
+    /// Returns the default mainnet profile.
+    pub fn mainnet() -> Self {
+        Self {
+            network: Network::Mainnet,
+            rpc_urls: vec![MAINNET_RPC_URL.to_string()],
+            archival_rpc_urls: vec![MAINNET_ARCHIVAL_RPC_URL.to_string()],
+            indexer_url: MAINNET_INDEXER_URL.to_string(),
+            rpc_api_key: None,
+            rpc_api_key_urls: Vec::new(),
+            indexer_api_key: None,
+        }
+    }
+
+    /// Returns the default testnet profile.
+    pub fn testnet() -> Self {
+        Self {
+            network: Network::Testnet,
+            rpc_urls: vec![TESTNET_RPC_URL.to_string()],
+            archival_rpc_urls: vec![TESTNET_ARCHIVAL_RPC_URL.to_string()],
+            indexer_url: TESTNET_INDEXER_URL.to_string(),
+            rpc_api_key: None,
+            rpc_api_key_urls: Vec::new(),
+            indexer_api_key: None,
+        }
+    }
+
+    /// Builds a profile from a network name and optional endpoint overrides.
+    ///
+    /// Overrides replace the defaults of the selected network. Passing RPC
+    /// URLs while leaving the network at mainnet keeps the mainnet indexer,
+    /// which is what you want for private mainnet nodes. When several RPC
+    /// URLs are given, `NearClient` fails over between them. A custom network
+    /// without archival URLs uses its RPC URLs for historical queries too.
+    ///
+    /// The RPC API key is only sent to the endpoints passed in `rpc_urls` and
+    /// `archival_rpc_urls`, so a paid provider's key does not reach the public
+    /// default endpoints; the indexer key is only sent to the indexer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(String)` if `network` is `Custom` and no RPC URL is given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use near_balance_monitor::network::{Network, NetworkConfig};
+    /// let config = NetworkConfig::resolve(
+    ///     Network::Custom,
+    ///     vec!["http://localhost:3030".to_string()],
+    ///     Vec::new(),
+    ///     None,
+    ///     Some("secret".to_string()),
+    ///     None,
+    /// )?;
+    /// assert_eq!(config.rpc_urls, vec!["http://localhost:3030"]);
+    /// assert_eq!(config.archival_rpc_urls, vec!["http://localhost:3030"]);
+    /// assert_eq!(config.rpc_api_key_for("http://localhost:3030"), Some("secret"));
+    /// assert_eq!(config.rpc_api_key_for("https://rpc.mainnet.near.org"), None);
+    /// # Ok::<(), String>(())
+    /// ```
+    pub fn resolve(
+        network: Network,
+        rpc_urls: Vec<String>,
+        archival_rpc_urls: Vec<String>,
+        indexer_url: Option<String>,
+        rpc_api_key: Option<String>,
+        indexer_api_key: Option<String>,
+    ) -> Result<Self, String> {
+        let mut config = match network {
+            Network::Mainnet => Self::mainnet(),
+            Network::Testnet => Self::testnet(),
+            Network::Custom => {
+                if rpc_urls.is_empty() {
+                    return Err("--rpc-url is required for the custom network".to_string());
+                }
+                Self {
+                    network: Network::Custom,
+                    rpc_urls: Vec::new(),
+                    archival_rpc_urls: rpc_urls.clone(),
+                    // Custom networks rarely have an indexer; fall back to mainnet's.
+                    indexer_url: MAINNET_INDEXER_URL.to_string(),
+                    rpc_api_key: None,
+                    rpc_api_key_urls: Vec::new(),
+                    indexer_api_key: None,
+                }
+            }
+        };
+
+        config.rpc_api_key_urls = rpc_urls.iter().chain(&archival_rpc_urls).cloned().collect();
+        if rpc_api_key.is_some() && config.rpc_api_key_urls.is_empty() {
+            log::warn!(
+                "RPC API key is only sent to endpoints given with --rpc-url or --archival-rpc-url; \
+                 none were given"
+            );
+        }
+        if !rpc_urls.is_empty() {
+            config.rpc_urls = rpc_urls;
+        }
+        if !archival_rpc_urls.is_empty() {
+            config.archival_rpc_urls = archival_rpc_urls;
+        }
+        if let Some(url) = indexer_url {
+            config.indexer_url = url.trim_end_matches('/').to_string();
+        }
+        config.rpc_api_key = rpc_api_key;
+        config.indexer_api_key = indexer_api_key;
+
+        log::debug!(
+            "Resolved network profile network={} rpc={:?} archival={:?} indexer={}",
+            config.network,
+            config.rpc_urls,
+            config.archival_rpc_urls,
+            config.indexer_url
+        );
+
+        Ok(config)
+    }
+
+    /// Returns the RPC API key to send to `endpoint`, if any.
+    pub fn rpc_api_key_for(&self, endpoint: &str) -> Option<&str> {
+        self.rpc_api_key
+            .as_deref()
+            .filter(|_| self.rpc_api_key_urls.iter().any(|url| url == endpoint))
+    }
+}
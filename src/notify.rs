@@ -0,0 +1,439 @@
+//! Alert delivery channels.
+//!
+//! The bot's monitoring loop hands every alert to a [`Notifier`]. Telegram
+//! is the default; a monitored account can instead route its alerts to a
+//! Slack incoming webhook, a Discord webhook or a Matrix room, chosen with
+//! `/notify` and stored as an [`AlertChannel`] with the account.
+//!
+//! Matrix needs a homeserver, an access token and the rooms the bot may post
+//! to, which are configured once for the bot ([`MatrixConfig`]); Slack and
+//! Discord webhook URLs carry their own credentials.
+
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use teloxide::prelude::*;
+
+use crate::error::Error;
+
+/// Maximum length of a Discord message, in characters.
+const DISCORD_MAX_MESSAGE_CHARS: usize = 2000;
+
+/// URL prefixes of Slack incoming webhooks.
+const SLACK_WEBHOOK_PREFIXES: &[&str] = &["https://hooks.slack.com/"];
+
+/// URL prefixes of Discord webhooks.
+const DISCORD_WEBHOOK_PREFIXES: &[&str] = &[
+    "https://discord.com/api/webhooks/",
+    "https://discordapp.com/api/webhooks/",
+];
+
+/// Time limit for one Slack, Discord or Matrix request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Destination for alert messages.
+///
+/// The method returns a boxed future so that notifiers can be chosen per
+/// account at runtime and used as `Box<dyn Notifier>`.
+pub trait Notifier: Send + Sync {
+    /// Short channel name, for log lines.
+    fn name(&self) -> &'static str;
+
+    /// Sends a plain-text message.
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+}
+
+/// Sends alerts to a Telegram chat.
+pub struct TelegramNotifier {
+    /// Bot used for sending.
+    bot: Bot,
+    /// Chat receiving the alerts.
+    chat_id: ChatId,
+}
+
+impl TelegramNotifier {
+    /// Creates a notifier for `chat_id`.
+    pub fn new(bot: Bot, chat_id: ChatId) -> Self {
+        Self { bot, chat_id }
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            self.bot
+                .send_message(self.chat_id, message)
+                .await
+                .map(|_| ())
+                .map_err(|e| Error::Transport(e.to_string()))
+        })
+    }
+}
+
+/// Sends alerts to a Slack incoming webhook.
+pub struct SlackNotifier {
+    /// HTTP client used for requests.
+    client: reqwest::Client,
+    /// Incoming webhook URL.
+    webhook_url: String,
+}
+
+impl SlackNotifier {
+    /// Creates a notifier for an incoming webhook URL.
+    pub fn new(client: reqwest::Client, webhook_url: &str) -> Self {
+        Self {
+            client,
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let request = self
+                .client
+                .post(&self.webhook_url)
+                .json(&serde_json::json!({ "text": message }));
+            send_request(request, "Slack webhook").await
+        })
+    }
+}
+
+/// Sends alerts to a Discord webhook.
+pub struct DiscordNotifier {
+    /// HTTP client used for requests.
+    client: reqwest::Client,
+    /// Webhook URL.
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    /// Creates a notifier for a webhook URL.
+    pub fn new(client: reqwest::Client, webhook_url: &str) -> Self {
+        Self {
+            client,
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            // Discord rejects longer messages instead of truncating them.
+            let content: String = if message.chars().count() > DISCORD_MAX_MESSAGE_CHARS {
+                let mut content: String = message
+                    .chars()
+                    .take(DISCORD_MAX_MESSAGE_CHARS - 1)
+                    .collect();
+                content.push('…');
+                content
+            } else {
+                message.to_string()
+            };
+            let request = self
+                .client
+                .post(&self.webhook_url)
+                .json(&serde_json::json!({ "content": content }));
+            send_request(request, "Discord webhook").await
+        })
+    }
+}
+
+/// Matrix homeserver account the bot posts alerts with.
+#[derive(Clone, Debug)]
+pub struct MatrixConfig {
+    /// Homeserver base URL, e.g. `https://matrix.org`.
+    pub homeserver: String,
+    /// Access token of the posting user, which must have joined the rooms.
+    pub access_token: String,
+    /// Room IDs alerts may be sent to. Without this allowlist, any chat
+    /// could make the bot post to any room its account has joined.
+    pub rooms: Vec<String>,
+}
+
+impl MatrixConfig {
+    /// Returns `true` if alerts may be sent to `room_id`.
+    pub fn allows_room(&self, room_id: &str) -> bool {
+        self.rooms.iter().any(|room| room == room_id)
+    }
+}
+
+/// Sends alerts to a Matrix room through the client-server API.
+pub struct MatrixNotifier {
+    /// HTTP client used for requests.
+    client: reqwest::Client,
+    /// Homeserver and credentials.
+    config: MatrixConfig,
+    /// Room ID, e.g. `!abc123:matrix.org`.
+    room_id: String,
+}
+
+impl MatrixNotifier {
+    /// Creates a notifier posting to `room_id`.
+    pub fn new(client: reqwest::Client, config: MatrixConfig, room_id: &str) -> Self {
+        Self {
+            client,
+            config,
+            room_id: room_id.to_string(),
+        }
+    }
+}
+
+impl Notifier for MatrixNotifier {
+    fn name(&self) -> &'static str {
+        "matrix"
+    }
+
+    fn send<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let mut url = reqwest::Url::parse(&self.config.homeserver).map_err(|e| {
+                Error::Config(format!(
+                    "invalid Matrix homeserver URL {}: {e}",
+                    self.config.homeserver
+                ))
+            })?;
+            // Matrix requires a transaction ID per event. A fresh one per
+            // call means a send that failed after reaching the homeserver is
+            // not deduplicated; alerts are not retried, so it stays one event.
+            let txn_id = format!("{:032x}", rand::random::<u128>());
+            url.path_segments_mut()
+                .map_err(|()| {
+                    Error::Config(format!(
+                        "invalid Matrix homeserver URL {}",
+                        self.config.homeserver
+                    ))
+                })?
+                .pop_if_empty()
+                .extend([
+                    "_matrix",
+                    "client",
+                    "v3",
+                    "rooms",
+                    self.room_id.as_str(),
+                    "send",
+                    "m.room.message",
+                    txn_id.as_str(),
+                ]);
+            let request = self
+                .client
+                .put(url)
+                .bearer_auth(&self.config.access_token)
+                .json(&serde_json::json!({ "msgtype": "m.text", "body": message }));
+            send_request(request, "Matrix homeserver").await
+        })
+    }
+}
+
+/// Sends a request and treats any non-success status as an error.
+async fn send_request(request: reqwest::RequestBuilder, target: &str) -> Result<(), Error> {
+    // Don't put webhook URLs in errors: they contain the credentials.
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Error::Transport(e.without_url().to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(Error::HttpStatus {
+            status: status.as_u16(),
+            url: target.to_string(),
+        })
+    }
+}
+
+/// Where the alerts of a monitored account are delivered.
+///
+/// Stored with each monitored account; entries persisted before channels
+/// existed deserialize as [`AlertChannel::Telegram`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AlertChannel {
+    /// The Telegram chat that added the account.
+    #[default]
+    Telegram,
+    /// A Slack incoming webhook.
+    Slack {
+        /// Incoming webhook URL.
+        webhook_url: String,
+    },
+    /// A Discord webhook.
+    Discord {
+        /// Webhook URL.
+        webhook_url: String,
+    },
+    /// A Matrix room, posted to with the bot's Matrix account.
+    Matrix {
+        /// Room ID, e.g. `!abc123:matrix.org`.
+        room_id: String,
+    },
+}
+
+impl AlertChannel {
+    /// Returns `true` for the default Telegram channel.
+    pub fn is_telegram(&self) -> bool {
+        *self == AlertChannel::Telegram
+    }
+
+    /// Parses the channel arguments of `/notify`: `telegram`,
+    /// `slack <webhook_url>`, `discord <webhook_url>` or `matrix <room_id>`.
+    ///
+    /// Webhook URLs must point at Slack or Discord, since any chat can set
+    /// them and the bot POSTs to them right away.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing the expected arguments.
+    pub fn parse(args: &[&str]) -> Result<Self, String> {
+        match args {
+            ["telegram"] => Ok(AlertChannel::Telegram),
+            ["slack", url] => {
+                check_webhook_url(url, "Slack", SLACK_WEBHOOK_PREFIXES)?;
+                Ok(AlertChannel::Slack {
+                    webhook_url: url.to_string(),
+                })
+            }
+            ["discord", url] => {
+                check_webhook_url(url, "Discord", DISCORD_WEBHOOK_PREFIXES)?;
+                Ok(AlertChannel::Discord {
+                    webhook_url: url.to_string(),
+                })
+            }
+            ["matrix", room_id] if room_id.starts_with('!') && room_id.contains(':') => {
+                Ok(AlertChannel::Matrix {
+                    room_id: room_id.to_string(),
+                })
+            }
+            ["matrix", _] => Err(
+                "Matrix room IDs look like !abc123:matrix.org (see the room settings).".to_string(),
+            ),
+            _ => Err(
+                "Choose telegram, slack <webhook_url>, discord <webhook_url> or matrix <room_id>."
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+/// Checks that a webhook URL starts with one of the `prefixes` of `service`.
+fn check_webhook_url(url: &str, service: &str, prefixes: &[&str]) -> Result<(), String> {
+    if prefixes.iter().any(|prefix| url.starts_with(prefix)) {
+        Ok(())
+    } else {
+        Err(format!(
+            "The {service} webhook URL must start with {}.",
+            prefixes.join(" or ")
+        ))
+    }
+}
+
+impl std::fmt::Display for AlertChannel {
+    /// Names the channel without revealing webhook URLs, which are secrets.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlertChannel::Telegram => write!(f, "Telegram"),
+            AlertChannel::Slack { .. } => write!(f, "Slack"),
+            AlertChannel::Discord { .. } => write!(f, "Discord"),
+            AlertChannel::Matrix { room_id } => write!(f, "Matrix room {room_id}"),
+        }
+    }
+}
+
+/// Builds the [`Notifier`] for an [`AlertChannel`].
+///
+/// Cheap to clone; clones share the bot and HTTP client.
+#[derive(Clone)]
+pub struct Notifiers {
+    /// Telegram bot, for the default channel.
+    bot: Bot,
+    /// HTTP client shared by the webhook and Matrix notifiers.
+    client: reqwest::Client,
+    /// Matrix account, if configured.
+    matrix: Option<MatrixConfig>,
+}
+
+impl Notifiers {
+    /// Creates the factory; Matrix channels need `matrix` to be set.
+    pub fn new(bot: Bot, matrix: Option<MatrixConfig>) -> Self {
+        Self {
+            bot,
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to build notifier HTTP client: {}", e);
+                    reqwest::Client::new()
+                }),
+            matrix,
+        }
+    }
+
+    /// Returns `true` if Matrix channels can be used.
+    pub fn has_matrix(&self) -> bool {
+        self.matrix.is_some()
+    }
+
+    /// Returns `true` if `room_id` is on the operator's Matrix allowlist.
+    pub fn allows_matrix_room(&self, room_id: &str) -> bool {
+        self.matrix
+            .as_ref()
+            .is_some_and(|matrix| matrix.allows_room(room_id))
+    }
+
+    /// Returns the notifier for `channel`; `chat_id` is the Telegram chat.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Config` error for Matrix channels if no Matrix account is
+    /// configured or the room is not on its allowlist, and for Slack or
+    /// Discord webhook URLs that do not point at Slack or Discord.
+    pub fn for_channel(
+        &self,
+        channel: &AlertChannel,
+        chat_id: ChatId,
+    ) -> Result<Box<dyn Notifier>, Error> {
+        Ok(match channel {
+            AlertChannel::Telegram => Box::new(TelegramNotifier::new(self.bot.clone(), chat_id)),
+            // Entries saved before URLs were restricted may point elsewhere.
+            AlertChannel::Slack { webhook_url } => {
+                check_webhook_url(webhook_url, "Slack", SLACK_WEBHOOK_PREFIXES)
+                    .map_err(Error::Config)?;
+                Box::new(SlackNotifier::new(self.client.clone(), webhook_url))
+            }
+            AlertChannel::Discord { webhook_url } => {
+                check_webhook_url(webhook_url, "Discord", DISCORD_WEBHOOK_PREFIXES)
+                    .map_err(Error::Config)?;
+                Box::new(DiscordNotifier::new(self.client.clone(), webhook_url))
+            }
+            AlertChannel::Matrix { room_id } => {
+                let config = self.matrix.clone().ok_or_else(|| {
+                    Error::Config(
+                        "Matrix is not configured (--matrix-homeserver, --matrix-token)"
+                            .to_string(),
+                    )
+                })?;
+                if !config.allows_room(room_id) {
+                    return Err(Error::Config(format!(
+                        "Matrix room {room_id} is not in --matrix-rooms"
+                    )));
+                }
+                Box::new(MatrixNotifier::new(self.client.clone(), config, room_id))
+            }
+        })
+    }
+}
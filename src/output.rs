@@ -0,0 +1,602 @@
+//! Machine-readable command output.
+//!
+//! With `--output json|ndjson|csv`, commands print the records defined here
+//! instead of human-readable text. The record fields form a documented,
+//! stable schema (see the README): fields may be added, but are never
+//! renamed or removed.
+//!
+//! NEAR amounts appear twice: `*_yocto` is the exact yoctoNEAR amount as a
+//! string (JSON numbers cannot hold it), `*_near` the same amount as an
+//! exact decimal string. In CSV, list fields are joined with `"; "` and
+//! missing values are empty.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::amount::{self, AmountFormat, NEAR_DECIMALS, NearAmount};
+use crate::check::{CheckResult, Thresholds};
+use crate::cli::OutputFormat;
+use crate::error::Error;
+use crate::near::{
+    AccessKey, AccessKeyPermission, AccountSnapshot, BalanceSample, ExecutionStatus, FtMetadata,
+    Receipt, StakingBalance, Transaction, TransactionStatus,
+};
+use crate::utils;
+
+/// Formats yoctoNEAR as an exact NEAR decimal without symbol, e.g. "1.5".
+pub fn near_decimal(yocto: u128) -> String {
+    NearAmount::from_yocto(yocto).format(
+        &AmountFormat::default()
+            .with_precision(NEAR_DECIMALS)
+            .trimmed()
+            .without_symbol(),
+    )
+}
+
+/// Converts a block timestamp in nanoseconds to RFC 3339.
+fn block_time(timestamp_ns: u64) -> String {
+    DateTime::from_timestamp_nanos(timestamp_ns as i64).to_rfc3339()
+}
+
+/// Native balance of an account (`balance`).
+#[derive(Serialize)]
+pub struct BalanceRecord {
+    pub account_id: String,
+    /// Current time, or the block time for historical balances (RFC 3339).
+    pub time: String,
+    pub block_height: u64,
+    pub block_hash: String,
+    pub balance_yocto: NearAmount,
+    pub balance_near: String,
+    pub locked_yocto: NearAmount,
+    pub locked_near: String,
+    pub storage_usage: u64,
+    pub storage_reserved_yocto: NearAmount,
+    pub storage_reserved_near: String,
+    pub available_yocto: NearAmount,
+    pub available_near: String,
+    pub code_hash: String,
+    /// Fiat currency of `fiat_price` and `fiat_value`, with `--fiat`.
+    pub fiat_currency: Option<String>,
+    /// Price of one NEAR in `fiat_currency`.
+    pub fiat_price: Option<f64>,
+    /// Value of `balance_near` in `fiat_currency`.
+    pub fiat_value: Option<f64>,
+}
+
+impl BalanceRecord {
+    /// Builds the record for a snapshot taken at `time`.
+    pub fn new(account_id: &str, time: DateTime<Utc>, account: &AccountSnapshot) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            time: time.to_rfc3339(),
+            block_height: account.block_height,
+            block_hash: account.block_hash.clone(),
+            balance_yocto: NearAmount::from_yocto(account.amount),
+            balance_near: near_decimal(account.amount),
+            locked_yocto: NearAmount::from_yocto(account.locked),
+            locked_near: near_decimal(account.locked),
+            storage_usage: account.storage_usage,
+            storage_reserved_yocto: NearAmount::from_yocto(account.storage_reserved()),
+            storage_reserved_near: near_decimal(account.storage_reserved()),
+            available_yocto: NearAmount::from_yocto(account.available()),
+            available_near: near_decimal(account.available()),
+            code_hash: account.code_hash.clone(),
+            fiat_currency: None,
+            fiat_price: None,
+            fiat_value: None,
+        }
+    }
+
+    /// Adds the fiat value of the balance at `price` per NEAR.
+    pub fn with_fiat(mut self, currency: &str, price: f64) -> Self {
+        self.fiat_currency = Some(currency.to_lowercase());
+        self.fiat_price = Some(price);
+        self.fiat_value = Some(crate::price::fiat_value(self.balance_yocto, price));
+        self
+    }
+}
+
+/// Fungible token balance of an account (`balance --token`).
+#[derive(Serialize)]
+pub struct TokenBalanceRecord {
+    pub account_id: String,
+    pub time: String,
+    pub token: String,
+    pub symbol: String,
+    pub decimals: u8,
+    /// Balance in the token's smallest unit, as a string.
+    pub balance_raw: String,
+    /// Balance as an exact decimal.
+    pub balance: String,
+}
+
+impl TokenBalanceRecord {
+    /// Builds the record for a raw token balance.
+    pub fn new(account_id: &str, token: &str, metadata: &FtMetadata, balance: u128) -> Self {
+        let decimals = u32::from(metadata.decimals);
+        Self {
+            account_id: account_id.to_string(),
+            time: Utc::now().to_rfc3339(),
+            token: token.to_string(),
+            symbol: metadata.symbol.clone(),
+            decimals: metadata.decimals,
+            balance_raw: balance.to_string(),
+            balance: amount::format_decimal(balance, decimals, decimals, true, false),
+        }
+    }
+}
+
+/// One transaction of an account (`txs`).
+#[derive(Serialize)]
+pub struct TransactionRecord {
+    pub hash: String,
+    /// Block time (RFC 3339); missing if the indexer sent an invalid value.
+    pub time: Option<String>,
+    pub signer_id: String,
+    pub receiver_id: String,
+    pub deposit_yocto: NearAmount,
+    pub deposit_near: String,
+    /// Decoded actions, e.g. "Transfer 1.0000 NEAR to bob.near".
+    pub actions: Vec<String>,
+    pub fiat_currency: Option<String>,
+    /// Value of the deposit at the current price.
+    pub fiat_value: Option<f64>,
+}
+
+impl TransactionRecord {
+    /// Builds the record for a transaction; `token` is its token metadata,
+    /// if it transfers fungible tokens.
+    pub fn new(tx: &Transaction, token: Option<&FtMetadata>) -> Self {
+        let deposit = tx.actions_agg.deposit;
+        Self {
+            hash: tx.hash.clone(),
+            time: tx.timestamp_ns().map(block_time),
+            signer_id: tx.signer_id.clone(),
+            receiver_id: tx.receiver_id.clone(),
+            deposit_yocto: deposit,
+            deposit_near: near_decimal(deposit.as_yocto()),
+            actions: tx
+                .actions
+                .iter()
+                .map(|action| utils::describe_action(action, &tx.receiver_id, token))
+                .collect(),
+            fiat_currency: None,
+            fiat_value: None,
+        }
+    }
+
+    /// Adds the fiat value of the deposit at `price` per NEAR.
+    pub fn with_fiat(mut self, currency: &str, price: f64) -> Self {
+        self.fiat_currency = Some(currency.to_lowercase());
+        self.fiat_value = Some(crate::price::fiat_value(self.deposit_yocto, price));
+        self
+    }
+}
+
+/// Status of a transaction (`tx`).
+#[derive(Serialize)]
+pub struct TransactionStatusRecord {
+    pub hash: String,
+    pub signer_id: String,
+    pub receiver_id: String,
+    /// "success", "failure" or "pending".
+    pub status: &'static str,
+    /// Result or error message.
+    pub result: String,
+    pub gas_burnt: u64,
+    pub tokens_burnt_yocto: NearAmount,
+    pub tokens_burnt_near: String,
+    pub actions: Vec<String>,
+    pub receipts: Vec<ReceiptRecord>,
+}
+
+/// One executed receipt of a transaction.
+#[derive(Serialize)]
+pub struct ReceiptRecord {
+    pub receipt_id: String,
+    pub predecessor_id: Option<String>,
+    pub executor_id: String,
+    pub status: &'static str,
+    pub result: String,
+    pub gas_burnt: u64,
+    pub tokens_burnt_yocto: NearAmount,
+    pub actions: Vec<String>,
+    pub logs: Vec<String>,
+}
+
+/// Short status name of an execution.
+fn status_name(status: &ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Pending => "pending",
+        ExecutionStatus::Failure(_) => "failure",
+        ExecutionStatus::SuccessValue(_) | ExecutionStatus::SuccessReceipt(_) => "success",
+    }
+}
+
+impl From<&TransactionStatus> for TransactionStatusRecord {
+    fn from(status: &TransactionStatus) -> Self {
+        let receipts = status
+            .receipts_outcome
+            .iter()
+            .map(|outcome| {
+                let receipt: Option<&Receipt> = status.receipt(&outcome.id);
+                ReceiptRecord {
+                    receipt_id: outcome.id.clone(),
+                    predecessor_id: receipt.map(|receipt| receipt.predecessor_id.clone()),
+                    executor_id: outcome.executor_id.clone(),
+                    status: status_name(&outcome.status),
+                    result: utils::format_execution_status(&outcome.status),
+                    gas_burnt: outcome.gas_burnt,
+                    tokens_burnt_yocto: NearAmount::from_yocto(outcome.tokens_burnt),
+                    actions: receipt
+                        .map(|receipt| receipt.actions.iter().map(utils::format_action).collect())
+                        .unwrap_or_default(),
+                    logs: outcome.logs.clone(),
+                }
+            })
+            .collect();
+        Self {
+            hash: status.hash.clone(),
+            signer_id: status.signer_id.clone(),
+            receiver_id: status.receiver_id.clone(),
+            status: status_name(&status.status),
+            result: utils::format_execution_status(&status.status),
+            gas_burnt: status.gas_burnt(),
+            tokens_burnt_yocto: NearAmount::from_yocto(status.tokens_burnt()),
+            tokens_burnt_near: near_decimal(status.tokens_burnt()),
+            actions: status.actions.iter().map(utils::format_action).collect(),
+            receipts,
+        }
+    }
+}
+
+/// Delegation to a staking pool (`staking`).
+#[derive(Serialize)]
+pub struct StakingRecord {
+    pub account_id: String,
+    pub pool: String,
+    pub staked_yocto: NearAmount,
+    pub staked_near: String,
+    pub unstaked_yocto: NearAmount,
+    pub unstaked_near: String,
+    pub total_yocto: NearAmount,
+    pub total_near: String,
+    pub unstaked_available: bool,
+}
+
+impl StakingRecord {
+    /// Builds the record for a delegation of `account_id` to `pool`.
+    pub fn new(account_id: &str, pool: &str, staking: &StakingBalance) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            pool: pool.to_string(),
+            staked_yocto: NearAmount::from_yocto(staking.staked),
+            staked_near: near_decimal(staking.staked),
+            unstaked_yocto: NearAmount::from_yocto(staking.unstaked),
+            unstaked_near: near_decimal(staking.unstaked),
+            total_yocto: NearAmount::from_yocto(staking.total()),
+            total_near: near_decimal(staking.total()),
+            unstaked_available: staking.unstaked_available,
+        }
+    }
+}
+
+/// One access key (`keys`).
+#[derive(Serialize)]
+pub struct AccessKeyRecord {
+    pub public_key: String,
+    /// "full_access" or "function_call".
+    pub permission: &'static str,
+    /// Contract a function call key may call.
+    pub receiver_id: Option<String>,
+    /// Methods a function call key may call; empty means any.
+    pub method_names: Vec<String>,
+    /// Remaining allowance of a function call key; missing means unlimited.
+    pub allowance_yocto: Option<NearAmount>,
+}
+
+impl From<&AccessKey> for AccessKeyRecord {
+    fn from(key: &AccessKey) -> Self {
+        let (permission, receiver_id, method_names, allowance) = match &key.permission {
+            AccessKeyPermission::FullAccess => ("full_access", None, Vec::new(), None),
+            AccessKeyPermission::FunctionCall {
+                allowance,
+                receiver_id,
+                method_names,
+            } => (
+                "function_call",
+                Some(receiver_id.clone()),
+                method_names.clone(),
+                allowance.map(NearAmount::from_yocto),
+            ),
+        };
+        Self {
+            public_key: key.public_key.clone(),
+            permission,
+            receiver_id,
+            method_names,
+            allowance_yocto: allowance,
+        }
+    }
+}
+
+/// One sample of a balance history (`history`).
+///
+/// Amounts are empty/`null` before the account existed.
+#[derive(Serialize)]
+pub struct HistoryRecord {
+    pub time: String,
+    pub block_height: u64,
+    pub block_timestamp: u64,
+    pub balance_yocto: Option<NearAmount>,
+    pub balance_near: Option<String>,
+}
+
+impl From<&BalanceSample> for HistoryRecord {
+    fn from(sample: &BalanceSample) -> Self {
+        let amount = sample
+            .account
+            .as_ref()
+            .map(|account| NearAmount::from_yocto(account.amount));
+        Self {
+            time: sample.at.to_rfc3339(),
+            block_height: sample.block.height,
+            block_timestamp: sample.block.timestamp,
+            balance_yocto: amount,
+            balance_near: amount.map(|amount| near_decimal(amount.as_yocto())),
+        }
+    }
+}
+
+/// Result of a balance probe (`check`).
+#[derive(Serialize)]
+pub struct CheckRecord {
+    pub account_id: String,
+    pub time: String,
+    /// "ok", "warning", "critical" or "unknown".
+    pub status: String,
+    /// Exit code of the command (0 to 3).
+    pub exit_code: i32,
+    pub balance_yocto: Option<NearAmount>,
+    pub balance_near: Option<String>,
+    pub warn_below_yocto: Option<NearAmount>,
+    pub crit_below_yocto: Option<NearAmount>,
+    /// Why the balance could not be fetched.
+    pub error: Option<String>,
+}
+
+impl CheckRecord {
+    /// Builds the record for a check result.
+    pub fn new(account_id: &str, result: &CheckResult, thresholds: &Thresholds) -> Self {
+        Self {
+            account_id: account_id.to_string(),
+            time: Utc::now().to_rfc3339(),
+            status: result.status.label().to_lowercase(),
+            exit_code: result.status.exit_code(),
+            balance_yocto: result.balance.map(NearAmount::from_yocto),
+            balance_near: result.balance.map(near_decimal),
+            warn_below_yocto: thresholds.warn_below.map(NearAmount::from_yocto),
+            crit_below_yocto: thresholds.crit_below.map(NearAmount::from_yocto),
+            error: result.error.clone(),
+        }
+    }
+}
+
+/// A reported change or a failed poll of `monitor`.
+///
+/// Both event types share one record so that CSV rows have a fixed set of
+/// columns; fields that do not apply are `null`/empty.
+#[derive(Serialize)]
+pub struct MonitorEvent {
+    /// "change" or "error".
+    pub event: &'static str,
+    pub time: String,
+    pub account_id: String,
+    pub balance_yocto: Option<NearAmount>,
+    pub balance_near: Option<String>,
+    pub locked_yocto: Option<NearAmount>,
+    /// Last reported balance; missing for the first report.
+    pub previous_yocto: Option<NearAmount>,
+    /// Signed change since the last reported balance, in yoctoNEAR.
+    pub delta_yocto: Option<String>,
+    /// Signed change as a NEAR decimal.
+    pub delta_near: Option<String>,
+    pub fiat_currency: Option<String>,
+    pub fiat_value: Option<f64>,
+    /// Error kind (see `Error::kind`), for errors.
+    pub error_kind: Option<&'static str>,
+    pub error: Option<String>,
+}
+
+impl MonitorEvent {
+    /// A reported change from `previous` (if any) to the snapshot's balance.
+    pub fn change(account_id: &str, account: &AccountSnapshot, previous: Option<u128>) -> Self {
+        let sign = |decreased: bool| if decreased { "-" } else { "" };
+        let delta =
+            previous.map(|previous| (account.amount < previous, account.amount.abs_diff(previous)));
+        Self {
+            event: "change",
+            time: Utc::now().to_rfc3339(),
+            account_id: account_id.to_string(),
+            balance_yocto: Some(NearAmount::from_yocto(account.amount)),
+            balance_near: Some(near_decimal(account.amount)),
+            locked_yocto: Some(NearAmount::from_yocto(account.locked)),
+            previous_yocto: previous.map(NearAmount::from_yocto),
+            delta_yocto: delta.map(|(decreased, delta)| format!("{}{}", sign(decreased), delta)),
+            delta_near: delta
+                .map(|(decreased, delta)| format!("{}{}", sign(decreased), near_decimal(delta))),
+            fiat_currency: None,
+            fiat_value: None,
+            error_kind: None,
+            error: None,
+        }
+    }
+
+    /// A failed poll of an account.
+    pub fn error(account_id: &str, error: &Error) -> Self {
+        Self {
+            event: "error",
+            time: Utc::now().to_rfc3339(),
+            account_id: account_id.to_string(),
+            balance_yocto: None,
+            balance_near: None,
+            locked_yocto: None,
+            previous_yocto: None,
+            delta_yocto: None,
+            delta_near: None,
+            fiat_currency: None,
+            fiat_value: None,
+            error_kind: Some(error.kind()),
+            error: Some(error.to_string()),
+        }
+    }
+
+    /// Adds the fiat value of the balance at `price` per NEAR.
+    pub fn with_fiat(mut self, currency: &str, price: f64) -> Self {
+        self.fiat_currency = Some(currency.to_lowercase());
+        self.fiat_value = self
+            .balance_yocto
+            .map(|balance| crate::price::fiat_value(balance, price));
+        self
+    }
+}
+
+/// Prints a single record: a JSON object, one NDJSON line, or a CSV header
+/// and row.
+///
+/// # Errors
+///
+/// Returns a `Parse` error if the record cannot be serialized.
+pub fn print_record<T: Serialize>(format: OutputFormat, record: &T) -> Result<(), Error> {
+    match format {
+        OutputFormat::Json => println!("{}", to_json(record, true)?),
+        _ => print_records(format, std::slice::from_ref(record))?,
+    }
+    Ok(())
+}
+
+/// Prints a list of records: a JSON array, one NDJSON line per record, or a
+/// CSV header and one row per record.
+///
+/// # Errors
+///
+/// Returns a `Parse` error if a record cannot be serialized.
+pub fn print_records<T: Serialize>(format: OutputFormat, records: &[T]) -> Result<(), Error> {
+    match format {
+        OutputFormat::Text => {}
+        OutputFormat::Json => println!("{}", to_json(&records, true)?),
+        OutputFormat::Ndjson => {
+            for record in records {
+                println!("{}", to_json(record, false)?);
+            }
+        }
+        OutputFormat::Csv => {
+            let mut writer = EventWriter::new(format);
+            for record in records {
+                writer.write(record)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes a stream of records as they happen, e.g. `monitor` events.
+///
+/// JSON and NDJSON both produce one compact object per line; CSV prints the
+/// header before the first row.
+pub struct EventWriter {
+    /// Output format.
+    format: OutputFormat,
+    /// Whether the CSV header was printed.
+    header_written: bool,
+}
+
+impl EventWriter {
+    /// Creates a writer printing to stdout.
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            header_written: false,
+        }
+    }
+
+    /// Prints one record.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Parse` error if the record cannot be serialized, or an
+    /// `Io` error if it cannot be written.
+    pub fn write<T: Serialize>(&mut self, record: &T) -> Result<(), Error> {
+        match self.format {
+            OutputFormat::Text => {}
+            OutputFormat::Json | OutputFormat::Ndjson => println!("{}", to_json(record, false)?),
+            OutputFormat::Csv => {
+                let value = serde_json::to_value(record)
+                    .map_err(|e| Error::Parse(format!("failed to serialize output: {e}")))?;
+                let mut columns = Vec::new();
+                flatten_csv("", &value, &mut columns);
+                let mut writer = csv::Writer::from_writer(std::io::stdout());
+                if !self.header_written {
+                    writer
+                        .write_record(columns.iter().map(|(name, _)| name))
+                        .map_err(|e| Error::Io(format!("failed to write CSV: {e}")))?;
+                    self.header_written = true;
+                }
+                writer
+                    .write_record(columns.iter().map(|(_, value)| value))
+                    .map_err(|e| Error::Io(format!("failed to write CSV: {e}")))?;
+                writer
+                    .flush()
+                    .map_err(|e| Error::Io(format!("failed to write CSV: {e}")))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a record as (pretty) JSON.
+fn to_json<T: Serialize + ?Sized>(record: &T, pretty: bool) -> Result<String, Error> {
+    let json = if pretty {
+        serde_json::to_string_pretty(record)
+    } else {
+        serde_json::to_string(record)
+    };
+    json.map_err(|e| Error::Parse(format!("failed to serialize output: {e}")))
+}
+
+/// Flattens a JSON value into CSV columns: nested object fields become
+/// `parent_child` columns, lists are joined with `"; "`, `null` is empty.
+fn flatten_csv(name: &str, value: &Value, columns: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(fields) => {
+            for (field, value) in fields {
+                let name = if name.is_empty() {
+                    field.clone()
+                } else {
+                    format!("{name}_{field}")
+                };
+                flatten_csv(&name, value, columns);
+            }
+        }
+        _ => columns.push((name.to_string(), csv_cell(value))),
+    }
+}
+
+/// Formats a JSON value as one CSV cell.
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("; "),
+        other => other.to_string(),
+    }
+}
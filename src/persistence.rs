@@ -11,7 +11,10 @@ use std::fs;
 use std::path::Path;
 use teloxide::types::ChatId;
 
-use crate::bot::MonitoredAccount;
+use crate::alert::{AlertRules, FloorGuard};
+use crate::bot::{MonitoredAccount, WatchKind};
+use crate::near::{AccessKey, StakingBalance};
+use crate::notify::AlertChannel;
 
 /// Manages persistence of monitored accounts to a JSON file.
 ///
@@ -26,7 +29,17 @@ use crate::bot::MonitoredAccount;
 /// [
 ///   {
 ///     "account_id": "example.near",
+///     "kind": { "type": "native" },
 ///     "last_balance": 1500000000000000000000000,
+///     "rules": { "min_delta": 1000000000000000000000000, "direction": "out" },
+///     "floor": { "floor": 5000000000000000000000000, "reminder_secs": 3600 },
+///     "channel": { "type": "slack", "webhook_url": "https://hooks.slack.com/services/..." },
+///     "chat_id": 123456789
+///   },
+///   {
+///     "account_id": "example.near",
+///     "kind": { "type": "token", "contract": "usdt.tether-token.near" },
+///     "last_balance": 50000000,
 ///     "chat_id": 123456789
 ///   }
 /// ]
@@ -114,7 +127,7 @@ impl AccountPersistenceManager {
     /// Adds a new monitored account to the system.
     ///
     /// Returns `true` if the account was newly added, `false` if it was already
-    /// being monitored by this user (duplicate check based on account_id + chat_id).
+    /// being monitored by this user (duplicate check based on account_id + kind + chat_id).
     /// Automatically saves the updated account list to disk.
     ///
     /// # Arguments
@@ -137,11 +150,11 @@ impl AccountPersistenceManager {
     /// ```
     pub fn add_account(&mut self, account: MonitoredAccount) -> bool {
         // Check for duplicates
-        if self
-            .accounts
-            .iter()
-            .any(|a| a.account_id == account.account_id && a.chat_id == account.chat_id)
-        {
+        if self.accounts.iter().any(|a| {
+            a.account_id == account.account_id
+                && a.kind == account.kind
+                && a.chat_id == account.chat_id
+        }) {
             log::debug!(
                 "Account already exists chat_id={} account={}",
                 account.chat_id,
@@ -162,13 +175,14 @@ impl AccountPersistenceManager {
 
     /// Removes a monitored account from the system.
     ///
-    /// The account is identified by both account_id and chat_id to ensure
+    /// The account is identified by account_id, kind and chat_id to ensure
     /// we only remove the specific user's monitoring entry.
     /// Automatically saves the updated account list to disk.
     ///
     /// # Arguments
     ///
     /// * `account_id` - The NEAR account ID to remove
+    /// * `kind` - What is watched on the account (native NEAR or a token)
     /// * `chat_id` - The Telegram chat ID of the user
     ///
     /// # Returns
@@ -179,14 +193,15 @@ impl AccountPersistenceManager {
     ///
     /// ```no_run
     /// # use near_balance_monitor::persistence::AccountPersistenceManager;
+    /// # use near_balance_monitor::bot::WatchKind;
     /// # use teloxide::types::ChatId;
     /// let mut manager = AccountPersistenceManager::load("monitored_accounts.json");
-    /// let removed = manager.remove_account("example.near", ChatId(123456789));
+    /// let removed = manager.remove_account("example.near", &WatchKind::Native, ChatId(123456789));
     /// ```
-    pub fn remove_account(&mut self, account_id: &str, chat_id: ChatId) -> bool {
+    pub fn remove_account(&mut self, account_id: &str, kind: &WatchKind, chat_id: ChatId) -> bool {
         let len_before = self.accounts.len();
         self.accounts
-            .retain(|a| !(a.account_id == account_id && a.chat_id == chat_id));
+            .retain(|a| !(a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id));
 
         let removed = self.accounts.len() < len_before;
         if removed {
@@ -205,7 +220,8 @@ impl AccountPersistenceManager {
 
     /// Updates an existing account's ID.
     ///
-    /// Finds the account by old ID and chat ID, then updates the account_id field.
+    /// Finds all entries with the old ID for the chat (native and token
+    /// watches alike), then updates their account_id field.
     /// The last_balance is reset to `None` to trigger a fresh balance check.
     /// Automatically saves the updated account list to disk.
     ///
@@ -238,19 +254,35 @@ impl AccountPersistenceManager {
         chat_id: ChatId,
         new_id: String,
     ) -> Result<(), String> {
-        if let Some(account) = self
+        let mut updated = 0;
+        for account in self
             .accounts
             .iter_mut()
-            .find(|a| a.account_id == old_id && a.chat_id == chat_id)
+            .filter(|a| a.account_id == old_id && a.chat_id == chat_id)
         {
+            account.account_id = new_id.clone();
+            // What was observed on the old account says nothing about the
+            // new one; start over so the next poll records a fresh baseline.
+            account.last_balance = None;
+            account.last_balance_at = None;
+            account.reported_transactions.clear();
+            account.last_staking = None;
+            account.last_keys = None;
+            if let Some(floor) = &mut account.floor {
+                floor.below_since = None;
+                floor.last_alert = None;
+            }
+            updated += 1;
+        }
+
+        if updated > 0 {
             log::info!(
-                "Account updated chat_id={} old={} new={}",
+                "Account updated chat_id={} old={} new={} entries={}",
                 chat_id,
                 old_id,
-                new_id
+                new_id,
+                updated
             );
-            account.account_id = new_id;
-            account.last_balance = None; // Reset to trigger new check
             self.save();
             Ok(())
         } else {
@@ -266,14 +298,18 @@ impl AccountPersistenceManager {
     /// Updates the last known balance for a monitored account.
     ///
     /// This is called by the background monitoring loop when a balance change
-    /// is detected. The update is only performed if the balance has actually changed.
+    /// is detected. The update is only performed if the balance has actually changed,
+    /// and records the time of the update in `last_balance_at` and the hashes
+    /// of the transactions the alert listed in `reported_transactions`.
     /// Automatically saves the updated account list to disk.
     ///
     /// # Arguments
     ///
     /// * `account_id` - The NEAR account ID to update
+    /// * `kind` - What is watched on the account (native NEAR or a token)
     /// * `chat_id` - The Telegram chat ID of the user
-    /// * `balance` - The new balance in yoctoNEAR
+    /// * `balance` - The new balance in yoctoNEAR (or the token's smallest unit)
+    /// * `reported_transactions` - Hashes of the transactions listed in the alert
     ///
     /// # Returns
     ///
@@ -283,15 +319,29 @@ impl AccountPersistenceManager {
     ///
     /// ```no_run
     /// # use near_balance_monitor::persistence::AccountPersistenceManager;
+    /// # use near_balance_monitor::bot::WatchKind;
     /// # use teloxide::types::ChatId;
     /// let mut manager = AccountPersistenceManager::load("monitored_accounts.json");
-    /// let updated = manager.update_balance("example.near", ChatId(123456789), 1500000000000000000000000);
+    /// let updated = manager.update_balance(
+    ///     "example.near",
+    ///     &WatchKind::Native,
+    ///     ChatId(123456789),
+    ///     1500000000000000000000000,
+    ///     Vec::new(),
+    /// );
     /// ```
-    pub fn update_balance(&mut self, account_id: &str, chat_id: ChatId, balance: u128) -> bool {
+    pub fn update_balance(
+        &mut self,
+        account_id: &str,
+        kind: &WatchKind,
+        chat_id: ChatId,
+        balance: u128,
+        reported_transactions: Vec<String>,
+    ) -> bool {
         if let Some(account) = self
             .accounts
             .iter_mut()
-            .find(|a| a.account_id == account_id && a.chat_id == chat_id)
+            .find(|a| a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id)
         {
             // Only save if balance actually changed
             if account.last_balance != Some(balance) {
@@ -302,6 +352,8 @@ impl AccountPersistenceManager {
                     balance
                 );
                 account.last_balance = Some(balance);
+                account.last_balance_at = Some(chrono::Utc::now().timestamp());
+                account.reported_transactions = reported_transactions;
                 self.save();
             }
             true
@@ -315,6 +367,237 @@ impl AccountPersistenceManager {
         }
     }
 
+    /// Updates the last known delegation state of a staking pool entry.
+    ///
+    /// Also sets `last_balance` to the total held in the pool.
+    /// Automatically saves the updated account list to disk.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the account was found and updated, `false` otherwise.
+    pub fn update_staking(
+        &mut self,
+        account_id: &str,
+        kind: &WatchKind,
+        chat_id: ChatId,
+        staking: StakingBalance,
+    ) -> bool {
+        if let Some(account) = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id)
+        {
+            log::debug!(
+                "Staking updated account={} chat_id={} staked={} unstaked={}",
+                account_id,
+                chat_id,
+                staking.staked,
+                staking.unstaked
+            );
+            account.last_balance = Some(staking.total());
+            account.last_staking = Some(staking);
+            self.save();
+            true
+        } else {
+            log::warn!(
+                "Account not found for staking update chat_id={} account={}",
+                chat_id,
+                account_id
+            );
+            false
+        }
+    }
+
+    /// Updates the last known access keys of a [`WatchKind::Keys`] entry.
+    ///
+    /// Automatically saves the updated account list to disk.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the account was found and updated, `false` otherwise.
+    pub fn update_keys(&mut self, account_id: &str, chat_id: ChatId, keys: Vec<AccessKey>) -> bool {
+        if let Some(account) = self.accounts.iter_mut().find(|a| {
+            a.account_id == account_id && a.kind == WatchKind::Keys && a.chat_id == chat_id
+        }) {
+            log::debug!(
+                "Access keys updated account={} chat_id={} count={}",
+                account_id,
+                chat_id,
+                keys.len()
+            );
+            account.last_keys = Some(keys);
+            self.save();
+            true
+        } else {
+            log::warn!(
+                "Account not found for access key update chat_id={} account={}",
+                chat_id,
+                account_id
+            );
+            false
+        }
+    }
+
+    /// Replaces the alert rules of an entry.
+    ///
+    /// Automatically saves the updated account list to disk.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the account was found and updated, `false` otherwise.
+    pub fn update_rules(
+        &mut self,
+        account_id: &str,
+        kind: &WatchKind,
+        chat_id: ChatId,
+        rules: AlertRules,
+    ) -> bool {
+        if let Some(account) = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id)
+        {
+            log::info!(
+                "Alert rules updated account={} chat_id={} rules={:?}",
+                account_id,
+                chat_id,
+                rules
+            );
+            account.rules = rules;
+            self.save();
+            true
+        } else {
+            log::warn!(
+                "Account not found for alert rules update chat_id={} account={}",
+                chat_id,
+                account_id
+            );
+            false
+        }
+    }
+
+    /// Sets or (with `None`) removes the low-balance guard of an entry.
+    ///
+    /// Automatically saves the updated account list to disk.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the account was found and updated, `false` otherwise.
+    pub fn update_floor(
+        &mut self,
+        account_id: &str,
+        kind: &WatchKind,
+        chat_id: ChatId,
+        floor: Option<FloorGuard>,
+    ) -> bool {
+        if let Some(account) = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id)
+        {
+            log::debug!(
+                "Floor guard updated account={} chat_id={} floor={:?}",
+                account_id,
+                chat_id,
+                floor
+            );
+            account.floor = floor;
+            self.save();
+            true
+        } else {
+            log::warn!(
+                "Account not found for floor update chat_id={} account={}",
+                chat_id,
+                account_id
+            );
+            false
+        }
+    }
+
+    /// Records the alert state (`below_since`, `last_alert`) of a low-balance
+    /// guard after an alert was delivered.
+    ///
+    /// `checked` is the guard the alert was computed from. The state is only
+    /// written if the stored guard still has the same floor and reminder
+    /// interval, so a `/floor` change made meanwhile is not overwritten.
+    /// Automatically saves the updated account list to disk.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the state was updated, `false` otherwise.
+    pub fn update_floor_state(
+        &mut self,
+        account_id: &str,
+        kind: &WatchKind,
+        chat_id: ChatId,
+        checked: &FloorGuard,
+        state: &FloorGuard,
+    ) -> bool {
+        let guard = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id)
+            .and_then(|a| a.floor.as_mut())
+            .filter(|g| g.floor == checked.floor && g.reminder_secs == checked.reminder_secs);
+        let Some(guard) = guard else {
+            log::debug!(
+                "Floor guard changed or removed, state not saved chat_id={} account={}",
+                chat_id,
+                account_id
+            );
+            return false;
+        };
+        guard.below_since = state.below_since;
+        guard.last_alert = state.last_alert;
+        log::debug!(
+            "Floor guard state updated account={} chat_id={} below_since={:?} last_alert={:?}",
+            account_id,
+            chat_id,
+            state.below_since,
+            state.last_alert
+        );
+        self.save();
+        true
+    }
+
+    /// Sets the alert channel of an entry.
+    ///
+    /// Automatically saves the updated account list to disk.
+    ///
+    /// # Returns
+    ///
+    /// Returns `true` if the account was found and updated, `false` otherwise.
+    pub fn update_channel(
+        &mut self,
+        account_id: &str,
+        kind: &WatchKind,
+        chat_id: ChatId,
+        channel: AlertChannel,
+    ) -> bool {
+        if let Some(account) = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.account_id == account_id && &a.kind == kind && a.chat_id == chat_id)
+        {
+            log::debug!(
+                "Alert channel updated account={} chat_id={} channel={}",
+                account_id,
+                chat_id,
+                channel
+            );
+            account.channel = channel;
+            self.save();
+            true
+        } else {
+            log::warn!(
+                "Account not found for channel update chat_id={} account={}",
+                chat_id,
+                account_id
+            );
+            false
+        }
+    }
+
     /// Returns all accounts being monitored by a specific user/chat.
     ///
     /// # Arguments
@@ -0,0 +1,258 @@
+//! Fiat prices of NEAR.
+//!
+//! A [`PriceProvider`] returns the price of one NEAR in a fiat currency.
+//! Two providers exist: [`CoinGeckoPrices`] queries a CoinGecko-compatible
+//! HTTP API, and [`StaticPrices`] serves fixed prices from memory or a JSON
+//! file, for offline use and tests. [`PriceCache`] wraps a provider and
+//! reuses prices for a configurable time.
+//!
+//! Currencies are lowercase codes as used by CoinGecko, e.g. `usd` or `eur`.
+
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::amount::{NEAR_DECIMALS, NearAmount};
+use crate::error::Error;
+
+/// Default CoinGecko API base URL.
+pub const COINGECKO_API_URL: &str = "https://api.coingecko.com/api/v3";
+
+/// CoinGecko ID of the NEAR token.
+const COINGECKO_NEAR_ID: &str = "near";
+
+/// Source of NEAR fiat prices.
+///
+/// The method returns a boxed future so that providers can be chosen at
+/// runtime and stored as `Box<dyn PriceProvider>`.
+pub trait PriceProvider: Send + Sync {
+    /// Short provider name, for log lines.
+    fn name(&self) -> &'static str;
+
+    /// Fetches the price of one NEAR in `currency` (a lowercase code).
+    fn near_price<'a>(&'a self, currency: &'a str) -> BoxFuture<'a, Result<f64, Error>>;
+}
+
+/// Prices from a CoinGecko-compatible `simple/price` endpoint.
+pub struct CoinGeckoPrices {
+    /// HTTP client used for requests.
+    client: reqwest::Client,
+    /// API base URL without trailing slash.
+    base_url: String,
+    /// Optional API key, sent as `x-cg-pro-api-key`.
+    api_key: Option<String>,
+}
+
+impl CoinGeckoPrices {
+    /// Creates a provider for the API at `base_url` (e.g. [`COINGECKO_API_URL`]).
+    pub fn new(base_url: &str, api_key: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+        }
+    }
+}
+
+impl PriceProvider for CoinGeckoPrices {
+    fn name(&self) -> &'static str {
+        "coingecko"
+    }
+
+    fn near_price<'a>(&'a self, currency: &'a str) -> BoxFuture<'a, Result<f64, Error>> {
+        Box::pin(async move {
+            let url = format!("{}/simple/price", self.base_url);
+            // `query` escapes the currency, which comes from user input.
+            let mut request = self
+                .client
+                .get(&url)
+                .query(&[("ids", COINGECKO_NEAR_ID), ("vs_currencies", currency)]);
+            if let Some(api_key) = &self.api_key {
+                request = request.header("x-cg-pro-api-key", api_key);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| Error::Transport(e.to_string()))?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(Error::HttpStatus {
+                    status: status.as_u16(),
+                    url,
+                });
+            }
+            // {"near": {"usd": 3.45}}; unknown currencies are simply missing.
+            let prices: HashMap<String, HashMap<String, f64>> = response
+                .json()
+                .await
+                .map_err(|e| Error::Parse(format!("unexpected price response: {e}")))?;
+            prices
+                .get(COINGECKO_NEAR_ID)
+                .and_then(|prices| prices.get(currency))
+                .copied()
+                .ok_or_else(|| Error::Config(format!("no NEAR price in currency '{currency}'")))
+        })
+    }
+}
+
+/// Fixed prices, e.g. loaded from a JSON file like `{"usd": 3.45, "eur": 3.1}`.
+pub struct StaticPrices {
+    /// Price of one NEAR by lowercase currency code.
+    prices: HashMap<String, f64>,
+}
+
+impl StaticPrices {
+    /// Creates a provider serving the given prices.
+    pub fn new(prices: HashMap<String, f64>) -> Self {
+        let prices = prices
+            .into_iter()
+            .map(|(currency, price)| (currency.to_lowercase(), price))
+            .collect();
+        Self { prices }
+    }
+
+    /// Loads prices from a JSON object mapping currency codes to prices.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Config` error if the file cannot be read or parsed.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            Error::Config(format!("failed to read price file {}: {e}", path.display()))
+        })?;
+        let prices: HashMap<String, f64> = serde_json::from_str(&data)
+            .map_err(|e| Error::Config(format!("invalid price file {}: {e}", path.display())))?;
+        log::info!(
+            "Loaded static prices file={} currencies={}",
+            path.display(),
+            prices.len()
+        );
+        Ok(Self::new(prices))
+    }
+}
+
+impl PriceProvider for StaticPrices {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    fn near_price<'a>(&'a self, currency: &'a str) -> BoxFuture<'a, Result<f64, Error>> {
+        let price = self
+            .prices
+            .get(currency)
+            .copied()
+            .ok_or_else(|| Error::Config(format!("no NEAR price in currency '{currency}'")));
+        Box::pin(async move { price })
+    }
+}
+
+/// Caches prices of a [`PriceProvider`] for a fixed time.
+///
+/// When a refresh fails, an expired price is still returned (with a warning)
+/// rather than dropping fiat values altogether.
+pub struct PriceCache {
+    /// Underlying price source.
+    provider: Box<dyn PriceProvider>,
+    /// How long a fetched price is reused.
+    ttl: Duration,
+    /// Last price and fetch time by currency.
+    entries: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+impl PriceCache {
+    /// Creates a cache reusing prices of `provider` for `ttl`.
+    pub fn new(provider: Box<dyn PriceProvider>, ttl: Duration) -> Self {
+        log::info!(
+            "Price provider configured provider={} ttl_secs={}",
+            provider.name(),
+            ttl.as_secs()
+        );
+        Self {
+            provider,
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the price of one NEAR in `currency`, case-insensitively.
+    ///
+    /// # Errors
+    ///
+    /// Returns the provider's [`Error`] if the price cannot be fetched and
+    /// no earlier price is cached.
+    pub async fn near_price(&self, currency: &str) -> Result<f64, Error> {
+        let currency = currency.to_lowercase();
+        let cached = self
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&currency)
+            .copied();
+        if let Some((price, fetched_at)) = cached {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(price);
+            }
+        }
+
+        match self.provider.near_price(&currency).await {
+            Ok(price) => {
+                log::debug!(
+                    "Fetched NEAR price provider={} currency={} price={}",
+                    self.provider.name(),
+                    currency,
+                    price
+                );
+                self.entries
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(currency, (price, Instant::now()));
+                Ok(price)
+            }
+            Err(e) => match cached {
+                Some((price, fetched_at)) => {
+                    log::warn!(
+                        "Price refresh failed, using cached price provider={} currency={} age_secs={}: {}",
+                        self.provider.name(),
+                        currency,
+                        fetched_at.elapsed().as_secs(),
+                        e
+                    );
+                    Ok(price)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Like [`near_price`](Self::near_price), but logs failures and returns
+    /// `None`.
+    ///
+    /// Fiat values are shown in addition to NEAR amounts, so a missing price
+    /// should not fail a command or an alert.
+    pub async fn try_near_price(&self, currency: &str) -> Option<f64> {
+        match self.near_price(currency).await {
+            Ok(price) => Some(price),
+            Err(e) => {
+                log::warn!(
+                    "Price fetch failed currency={} kind={}: {}",
+                    currency,
+                    e.kind(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Returns the fiat value of `amount` at `price` per NEAR.
+pub fn fiat_value(amount: NearAmount, price: f64) -> f64 {
+    // Split into whole NEAR and remainder so large balances keep their
+    // precision before the (inherently approximate) multiplication.
+    let unit = 10u128.pow(NEAR_DECIMALS);
+    let whole = (amount.as_yocto() / unit) as f64;
+    let fraction = (amount.as_yocto() % unit) as f64 / unit as f64;
+    (whole + fraction) * price
+}
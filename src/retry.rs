@@ -0,0 +1,113 @@
+//! Retry policy with exponential backoff and jitter.
+//!
+//! `NearClient` wraps every RPC and NearBlocks request in a [`RetryPolicy`].
+//! Transient failures (transport errors, HTTP 429/5xx, and NEAR's
+//! `TIMEOUT_ERROR`/`UNKNOWN_BLOCK`-style causes) are retried after a delay
+//! that doubles with each attempt, is capped at `max_delay`, and is spread
+//! by a random jitter so that many clients do not retry in lockstep.
+
+use std::time::Duration;
+
+/// NEAR RPC error causes that are worth retrying.
+///
+/// These indicate a node that is lagging, overloaded or still syncing rather
+/// than a problem with the request itself.
+const RETRYABLE_CAUSES: &[&str] = &[
+    "TIMEOUT_ERROR",
+    "UNKNOWN_BLOCK",
+    "UNKNOWN_CHUNK",
+    "NO_SYNCED_BLOCKS",
+    "NOT_SYNCED_YET",
+    "INTERNAL_ERROR",
+];
+
+/// Retry parameters for outgoing requests.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound for any single delay.
+    pub max_delay: Duration,
+    /// Random spread applied to each delay, as a fraction in `0.0..=1.0`.
+    /// A value of `0.5` yields delays between 50% and 100% of the backoff.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy, clamping `max_attempts` to at least 1 and `jitter`
+    /// to `0.0..=1.0`.
+    pub fn new(max_attempts: u32, base_delay: Duration, max_delay: Duration, jitter: f64) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            max_delay: max_delay.max(base_delay),
+            jitter: jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Returns the delay to wait after the given failed attempt (1-based).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use near_balance_monitor::retry::RetryPolicy;
+    /// let policy = RetryPolicy::new(5, Duration::from_millis(100), Duration::from_secs(1), 0.0);
+    /// assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+    /// assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+    /// assert_eq!(policy.delay_for(10), Duration::from_secs(1));
+    /// ```
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+        if self.jitter == 0.0 {
+            return backoff;
+        }
+        let factor = 1.0 - self.jitter * rand::random::<f64>();
+        backoff.mul_f64(factor)
+    }
+
+    /// Returns `true` if another attempt is allowed after `attempt` failed.
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+}
+
+/// Returns `true` if a NEAR RPC error object describes a transient failure.
+///
+/// Looks at both the top-level `name` and the `cause.name` of the error,
+/// e.g. `{"name": "HANDLER_ERROR", "cause": {"name": "UNKNOWN_BLOCK"}}`.
+pub fn is_retryable_rpc_error(error: &serde_json::Value) -> bool {
+    let name = error.get("name").and_then(|n| n.as_str());
+    let cause = error
+        .get("cause")
+        .and_then(|c| c.get("name"))
+        .and_then(|n| n.as_str());
+    [name, cause]
+        .into_iter()
+        .flatten()
+        .any(|n| RETRYABLE_CAUSES.contains(&n))
+}
+
+/// Returns `true` if an HTTP status code should be retried.
+pub fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
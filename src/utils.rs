@@ -3,11 +3,23 @@
 //! This module provides helper functions for converting between different
 //! representations of NEAR balances and timestamps.
 
-use chrono::{Local, TimeZone, Utc};
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, TimeZone, Utc};
+
+use crate::amount::{self, AmountFormat, NearAmount, Unit};
+use crate::near::{
+    AccessKey, AccessKeyPermission, AccountSnapshot, Action, ActionKind, ExecutionOutcome,
+    ExecutionStatus, FtMetadata, LockupInfo, StakingBalance, TransactionAction, TransactionStatus,
+    Vesting,
+};
+use crate::price;
+
+/// Maximum length of function call arguments shown by [`format_action`].
+const MAX_ARGS_LEN: usize = 120;
 
 /// Formats a yoctoNEAR balance into a human-readable NEAR string.
 ///
-/// Converts yoctoNEAR (10^24 yoctoNEAR = 1 NEAR) to NEAR with 4 decimal places.
+/// Converts yoctoNEAR (10^24 yoctoNEAR = 1 NEAR) to NEAR with 4 decimal places,
+/// rounding half up with exact integer arithmetic (see [`NearAmount`]).
 ///
 /// # Arguments
 ///
@@ -25,7 +37,482 @@ use chrono::{Local, TimeZone, Utc};
 /// assert_eq!(format_near(balance), "1.5000 NEAR");
 /// ```
 pub fn format_near(yocto: u128) -> String {
-    format!("{:.4} NEAR", yocto as f64 / crate::near::YOCTO_NEAR)
+    NearAmount::from_yocto(yocto).to_string()
+}
+
+/// Formats a fungible token amount using the token's decimals and symbol.
+///
+/// Uses integer arithmetic, so large balances are not rounded through `f64`.
+/// Amounts are rounded to at most 4 decimal places.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::utils::format_token;
+/// assert_eq!(format_token(50_000_000, 6, "USDC"), "50.0000 USDC");
+/// assert_eq!(format_token(1_234_567, 6, "USDC"), "1.2346 USDC");
+/// assert_eq!(format_token(15, 1, "X"), "1.5 X");
+/// ```
+pub fn format_token(amount: u128, decimals: u8, symbol: &str) -> String {
+    format!(
+        "{} {}",
+        amount::format_decimal(amount, u32::from(decimals), 4, false, false),
+        symbol
+    )
+}
+
+/// Formats a fiat value with two decimals, thousands separators and the
+/// uppercase currency code.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::utils::format_fiat;
+/// assert_eq!(format_fiat(1234.567, "usd"), "1,234.57 USD");
+/// assert_eq!(format_fiat(0.004, "eur"), "0.00 EUR");
+/// ```
+pub fn format_fiat(value: f64, currency: &str) -> String {
+    let cents = (value * 100.0).round().max(0.0) as u128;
+    format!(
+        "{} {}",
+        amount::format_decimal(cents, 2, 2, false, true),
+        currency.to_uppercase()
+    )
+}
+
+/// Formats the fiat value of a yoctoNEAR amount at `price` per NEAR,
+/// e.g. "≈ 3.45 USD".
+pub fn format_near_fiat(yocto: u128, price: f64, currency: &str) -> String {
+    format!(
+        "≈ {}",
+        format_fiat(
+            price::fiat_value(NearAmount::from_yocto(yocto), price),
+            currency
+        )
+    )
+}
+
+/// Formats an account snapshot as a multi-line balance breakdown.
+///
+/// Shows the total liquid balance, the locked (staked) balance, the part
+/// reserved for storage, the spendable balance, the deployed contract and
+/// the block the snapshot was taken at.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::near::AccountSnapshot;
+/// # use near_balance_monitor::utils::format_account_details;
+/// # let snapshot = AccountSnapshot {
+/// #     amount: 1_000_000_000_000_000_000_000_000,
+/// #     locked: 0,
+/// #     storage_usage: 182,
+/// #     code_hash: "11111111111111111111111111111111".to_string(),
+/// #     block_height: 1,
+/// #     block_hash: "hash".to_string(),
+/// #     storage_price_per_byte: 10_000_000_000_000_000_000,
+/// # };
+/// let details = format_account_details(&snapshot);
+/// assert!(details.starts_with("Total:            1.0000 NEAR"));
+/// ```
+pub fn format_account_details(snapshot: &AccountSnapshot) -> String {
+    let contract = if snapshot.has_contract() {
+        snapshot.code_hash.as_str()
+    } else {
+        "none"
+    };
+    format!(
+        "Total:            {}\nStaked/locked:    {}\nStorage reserved: {} ({} bytes)\nAvailable:        {}\nContract:         {}\nBlock:            #{} ({})",
+        format_near(snapshot.amount),
+        format_near(snapshot.locked),
+        format_near(snapshot.storage_reserved()),
+        snapshot.storage_usage,
+        format_near(snapshot.available()),
+        contract,
+        snapshot.block_height,
+        snapshot.block_hash
+    )
+}
+
+/// Formats a staking pool delegation as a multi-line breakdown.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::near::StakingBalance;
+/// # use near_balance_monitor::utils::format_staking_details;
+/// let staking = StakingBalance {
+///     staked: 2_000_000_000_000_000_000_000_000,
+///     unstaked: 0,
+///     unstaked_available: true,
+/// };
+/// let details = format_staking_details(&staking);
+/// assert!(details.starts_with("Staked:   2.0000 NEAR"));
+/// ```
+pub fn format_staking_details(staking: &StakingBalance) -> String {
+    let status = if staking.unstaked == 0 {
+        ""
+    } else if staking.unstaked_available {
+        " (withdrawable)"
+    } else {
+        " (pending withdrawal)"
+    };
+    format!(
+        "Staked:   {}\nUnstaked: {}{}\nTotal:    {}",
+        format_near(staking.staked),
+        format_near(staking.unstaked),
+        status,
+        format_near(staking.total())
+    )
+}
+
+/// Formats the state of a lockup contract as a multi-line breakdown.
+///
+/// `Liquid` is what the owner can withdraw now; `Locked` includes the
+/// unvested part.
+pub fn format_lockup_details(lockup: &LockupInfo) -> String {
+    let vesting = match &lockup.vesting {
+        Vesting::None => "none".to_string(),
+        Vesting::Hash(_) => "private schedule (hash only)".to_string(),
+        Vesting::Schedule {
+            start_timestamp,
+            cliff_timestamp,
+            end_timestamp,
+        } => format!(
+            "{} → cliff {} → {}",
+            format_timestamp(start_timestamp.to_string()),
+            format_timestamp(cliff_timestamp.to_string()),
+            format_timestamp(end_timestamp.to_string())
+        ),
+        Vesting::Terminating {
+            unvested_amount,
+            status,
+        } => format!(
+            "terminating ({}, {} unvested)",
+            status,
+            format_near(*unvested_amount)
+        ),
+    };
+    format!(
+        "Owner:            {}\nLockup balance:   {}\nLocked:           {}\nLiquid:           {}\nUnvested:         {}\nVesting:          {}\nStaking pool:     {}",
+        lockup.owner,
+        format_near(lockup.balance),
+        format_near(lockup.locked),
+        format_near(lockup.liquid),
+        format_near(lockup.unvested),
+        vesting,
+        lockup.staking_pool.as_deref().unwrap_or("none")
+    )
+}
+
+/// Formats an access key and its permission on one line.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::near::{AccessKey, AccessKeyPermission};
+/// # use near_balance_monitor::utils::format_access_key;
+/// let key = AccessKey {
+///     public_key: "ed25519:abc".to_string(),
+///     permission: AccessKeyPermission::FunctionCall {
+///         allowance: None,
+///         receiver_id: "app.near".to_string(),
+///         method_names: vec![],
+///     },
+/// };
+/// assert_eq!(
+///     format_access_key(&key),
+///     "ed25519:abc FunctionCall receiver=app.near methods=any allowance=unlimited"
+/// );
+/// ```
+pub fn format_access_key(key: &AccessKey) -> String {
+    match &key.permission {
+        AccessKeyPermission::FullAccess => format!("{} FullAccess", key.public_key),
+        AccessKeyPermission::FunctionCall {
+            allowance,
+            receiver_id,
+            method_names,
+        } => {
+            let methods = if method_names.is_empty() {
+                "any".to_string()
+            } else {
+                method_names.join(",")
+            };
+            let allowance = match allowance {
+                Some(allowance) => format_near(*allowance),
+                None => "unlimited".to_string(),
+            };
+            format!(
+                "{} FunctionCall receiver={} methods={} allowance={}",
+                key.public_key, receiver_id, methods, allowance
+            )
+        }
+    }
+}
+
+/// Formats an amount of gas in teragas.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::utils::format_gas;
+/// assert_eq!(format_gas(2_428_000_000_000), "2.43 Tgas");
+/// ```
+pub fn format_gas(gas: u64) -> String {
+    format!("{:.2} Tgas", gas as f64 / 1e12)
+}
+
+/// Formats a gas fee in milliNEAR, where typical fees are readable
+/// (`0.4860 mNEAR` rather than `0.0005 NEAR`).
+fn format_fee(yocto: u128) -> String {
+    NearAmount::from_yocto(yocto).format(&AmountFormat::default().with_unit(Unit::MilliNear))
+}
+
+/// Formats a transaction or receipt action on one line.
+///
+/// Function call arguments are shown as compact JSON when possible and
+/// shortened to a readable length.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::near::Action;
+/// # use near_balance_monitor::utils::format_action;
+/// let action = Action::FunctionCall {
+///     method_name: "ft_transfer".to_string(),
+///     args: br#"{"receiver_id": "bob.near", "amount": "50"}"#.to_vec(),
+///     gas: 30_000_000_000_000,
+///     deposit: NearAmount::from_yocto(1),
+/// };
+/// assert_eq!(
+///     format_action(&action),
+///     r#"FunctionCall ft_transfer({"amount":"50","receiver_id":"bob.near"}) gas=30.00 Tgas deposit=0.0000 NEAR"#
+/// );
+/// ```
+pub fn format_action(action: &Action) -> String {
+    match action {
+        Action::CreateAccount => "CreateAccount".to_string(),
+        Action::DeployContract { code_size } => format!("DeployContract ({code_size} bytes)"),
+        Action::FunctionCall {
+            method_name,
+            args,
+            gas,
+            deposit,
+        } => format!(
+            "FunctionCall {}({}) gas={} deposit={}",
+            method_name,
+            format_args_summary(args),
+            format_gas(*gas),
+            format_near(*deposit)
+        ),
+        Action::Transfer { deposit } => format!("Transfer {}", format_near(*deposit)),
+        Action::Stake { stake, public_key } => {
+            format!("Stake {} with {}", format_near(*stake), public_key)
+        }
+        Action::AddKey(key) => format!("AddKey {}", format_access_key(key)),
+        Action::DeleteKey { public_key } => format!("DeleteKey {public_key}"),
+        Action::DeleteAccount { beneficiary_id } => {
+            format!("DeleteAccount beneficiary={beneficiary_id}")
+        }
+        Action::Delegate {
+            sender_id,
+            receiver_id,
+            actions,
+        } => format!(
+            "Delegate {} -> {}: [{}]",
+            sender_id,
+            receiver_id,
+            actions
+                .iter()
+                .map(format_action)
+                .collect::<Vec<_>>()
+                .join("; ")
+        ),
+        Action::Other(name) => name.clone(),
+    }
+}
+
+/// Shortens function call arguments for display: compact JSON if they
+/// parse as JSON, the text if UTF-8, otherwise their size.
+fn format_args_summary(args: &[u8]) -> String {
+    match serde_json::from_slice::<serde_json::Value>(args) {
+        Ok(json) => format_json_summary(&json),
+        Err(_) => match std::str::from_utf8(args) {
+            Ok(text) => shorten(text),
+            Err(_) => format!("<{} bytes>", args.len()),
+        },
+    }
+}
+
+/// Formats JSON arguments compactly, shortened to a readable length.
+fn format_json_summary(args: &serde_json::Value) -> String {
+    match args {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(text) => shorten(text),
+        args => shorten(&args.to_string()),
+    }
+}
+
+/// Cuts `text` to [`MAX_ARGS_LEN`] characters, marking the cut with `…`.
+fn shorten(text: &str) -> String {
+    if text.chars().count() > MAX_ARGS_LEN {
+        let short: String = text.chars().take(MAX_ARGS_LEN).collect();
+        format!("{short}…")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Describes a NearBlocks transaction action in one line, e.g.
+/// `ft_transfer 50.0000 USDC to bob.near` or `Transfer 1.0000 NEAR to bob.near`.
+///
+/// `receiver_id` is the transaction's receiver. `token` is the metadata of
+/// the receiver contract for NEP-141 transfers (see
+/// [`NearClient::transaction_token`](crate::near::NearClient::transaction_token));
+/// without it the raw token amount is shown.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::amount::NearAmount;
+/// # use near_balance_monitor::near::{ActionKind, FtMetadata, TransactionAction};
+/// # use near_balance_monitor::utils::describe_action;
+/// let action = TransactionAction {
+///     kind: ActionKind::FunctionCall,
+///     method_name: Some("ft_transfer".to_string()),
+///     args: serde_json::json!({"receiver_id": "bob.near", "amount": "50000000"}),
+///     deposit: NearAmount::from_yocto(1),
+/// };
+/// let usdc = FtMetadata { name: "USD Coin".to_string(), symbol: "USDC".to_string(), decimals: 6 };
+/// assert_eq!(
+///     describe_action(&action, "usdc.near", Some(&usdc)),
+///     "ft_transfer 50.0000 USDC to bob.near"
+/// );
+/// ```
+pub fn describe_action(
+    action: &TransactionAction,
+    receiver_id: &str,
+    token: Option<&FtMetadata>,
+) -> String {
+    match &action.kind {
+        ActionKind::CreateAccount => format!("Create account {receiver_id}"),
+        ActionKind::DeployContract => format!("Deploy contract to {receiver_id}"),
+        ActionKind::Transfer => format!("Transfer {} to {}", action.deposit, receiver_id),
+        ActionKind::Stake => format!("Stake {}", action.deposit),
+        ActionKind::AddKey => format!("Add access key to {receiver_id}"),
+        ActionKind::DeleteKey => format!("Delete access key from {receiver_id}"),
+        ActionKind::DeleteAccount => format!("Delete account {receiver_id}"),
+        ActionKind::Delegate => format!("Relay meta transaction to {receiver_id}"),
+        ActionKind::Other(kind) => format!("{kind} on {receiver_id}"),
+        ActionKind::FunctionCall => {
+            let method = action.method_name.as_deref().unwrap_or("unknown method");
+            let token_amount = action
+                .args
+                .get("amount")
+                .and_then(|amount| amount.as_str())
+                .and_then(|amount| amount.parse::<u128>().ok());
+            let token_receiver = action.args.get("receiver_id").and_then(|r| r.as_str());
+            if let (true, Some(amount), Some(to)) =
+                (action.is_token_transfer(), token_amount, token_receiver)
+            {
+                let amount = match token {
+                    Some(token) => format_token(amount, token.decimals, &token.symbol),
+                    None => format!("{amount} (raw units of {receiver_id})"),
+                };
+                return format!("{method} {amount} to {to}");
+            }
+            let mut description = format!(
+                "{}({}) on {}",
+                method,
+                format_json_summary(&action.args),
+                receiver_id
+            );
+            // A 1 yoctoNEAR deposit is a security confirmation, not a payment.
+            if action.deposit.as_yocto() > 1 {
+                description.push_str(&format!(" with {}", action.deposit));
+            }
+            description
+        }
+    }
+}
+
+/// Formats an execution status, e.g. `Success`, `Success (value: "10")` or
+/// `Failure: ActionError: ...`.
+pub fn format_execution_status(status: &ExecutionStatus) -> String {
+    match status {
+        ExecutionStatus::Pending => "Pending".to_string(),
+        ExecutionStatus::SuccessValue(value) if value.is_empty() => "Success".to_string(),
+        ExecutionStatus::SuccessValue(value) => format!("Success (value: {value})"),
+        ExecutionStatus::SuccessReceipt(receipt_id) => {
+            format!("Success (continued in receipt {receipt_id})")
+        }
+        ExecutionStatus::Failure(error) => format!("Failure: {error}"),
+    }
+}
+
+/// Formats a transaction status as a multi-line report: the transaction's
+/// actions and totals, then every receipt with its actions, status, gas and
+/// logs.
+pub fn format_transaction_status(status: &TransactionStatus) -> String {
+    let mut lines = vec![
+        format!("Transaction:  {}", status.hash),
+        format!("Status:       {}", format_execution_status(&status.status)),
+        format!("Signer:       {}", status.signer_id),
+        format!("Receiver:     {}", status.receiver_id),
+        format!("Gas burnt:    {}", format_gas(status.gas_burnt())),
+        format!("Tokens burnt: {}", format_fee(status.tokens_burnt())),
+        "Actions:".to_string(),
+    ];
+    lines.extend(
+        status
+            .actions
+            .iter()
+            .map(|action| format!("  - {}", format_action(action))),
+    );
+    lines.push("Outcomes:".to_string());
+    for outcome in status.outcomes() {
+        let receipt = status.receipt(&outcome.id);
+        match receipt {
+            Some(receipt) => lines.push(format!(
+                "  - Receipt {} ({} -> {})",
+                outcome.id, receipt.predecessor_id, receipt.receiver_id
+            )),
+            None if outcome.id == status.hash => {
+                lines.push(format!("  - Transaction {}", outcome.id))
+            }
+            None => lines.push(format!(
+                "  - Receipt {} (on {})",
+                outcome.id, outcome.executor_id
+            )),
+        }
+        for action in receipt.map(|r| r.actions.as_slice()).unwrap_or_default() {
+            lines.push(format!("      Action: {}", format_action(action)));
+        }
+        lines.extend(format_outcome_details(outcome));
+    }
+    lines.join("\n")
+}
+
+/// Status, gas, produced receipts and logs of one outcome, indented below it.
+fn format_outcome_details(outcome: &ExecutionOutcome) -> Vec<String> {
+    let mut lines = vec![
+        format!("      Status: {}", format_execution_status(&outcome.status)),
+        format!(
+            "      Gas:    {} ({} burnt)",
+            format_gas(outcome.gas_burnt),
+            format_fee(outcome.tokens_burnt)
+        ),
+    ];
+    if !outcome.receipt_ids.is_empty() {
+        lines.push(format!("      Next:   {}", outcome.receipt_ids.join(", ")));
+    }
+    lines.extend(
+        outcome
+            .logs
+            .iter()
+            .map(|log| format!("      Log:    {log}")),
+    );
+    lines
 }
 
 /// Returns the current local time as a formatted string.
@@ -88,3 +575,103 @@ pub fn format_timestamp(ns_str: String) -> String {
         }
     }
 }
+
+/// Parses a point in time given on the command line or in a bot command.
+///
+/// Accepts RFC 3339 timestamps (`2026-09-30T23:59:59Z`) and plain dates
+/// (`2026-09-30`), which mean the last second of that day in UTC — the usual
+/// "balance at end of day" for accounting.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::utils::parse_datetime;
+/// let at = parse_datetime("2026-09-30").unwrap();
+/// assert_eq!(at.to_rfc3339(), "2026-09-30T23:59:59+00:00");
+/// assert!(parse_datetime("yesterday").is_err());
+/// ```
+pub fn parse_datetime(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_datetime_with_time(value, 23, 59, 59)
+}
+
+/// Parses the start of a time range, like [`parse_datetime`] except that a
+/// plain date means the first second of that day in UTC.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::utils::parse_start_datetime;
+/// let since = parse_start_datetime("2026-09-01").unwrap();
+/// assert_eq!(since.to_rfc3339(), "2026-09-01T00:00:00+00:00");
+/// ```
+pub fn parse_start_datetime(value: &str) -> Result<DateTime<Utc>, String> {
+    parse_datetime_with_time(value, 0, 0, 0)
+}
+
+/// Parses an RFC 3339 timestamp, or a plain date at the given time of day (UTC).
+fn parse_datetime_with_time(
+    value: &str,
+    hour: u32,
+    min: u32,
+    sec: u32,
+) -> Result<DateTime<Utc>, String> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(hour, min, sec))
+        .map(|datetime| datetime.and_utc())
+        .ok_or_else(|| format!("invalid date/time '{value}': expected RFC 3339 or YYYY-MM-DD"))
+}
+
+/// Parses a sampling interval such as `30m`, `12h`, `1d` or `1w`.
+///
+/// # Examples
+///
+/// ```
+/// # use chrono::TimeDelta;
+/// # use near_balance_monitor::utils::parse_step;
+/// assert_eq!(parse_step("1d"), Ok(TimeDelta::days(1)));
+/// assert_eq!(parse_step("12h"), Ok(TimeDelta::hours(12)));
+/// assert!(parse_step("0d").is_err());
+/// assert!(parse_step("1µ").is_err());
+/// ```
+pub fn parse_step(value: &str) -> Result<TimeDelta, String> {
+    let invalid = || format!("invalid step '{value}': expected e.g. 30m, 12h, 1d or 1w");
+    let (split, _) = value.char_indices().last().ok_or_else(invalid)?;
+    let (count, unit) = value.split_at(split);
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+    if count <= 0 {
+        return Err(invalid());
+    }
+    let step = match unit {
+        "m" => TimeDelta::try_minutes(count),
+        "h" => TimeDelta::try_hours(count),
+        "d" => TimeDelta::try_days(count),
+        "w" => TimeDelta::try_weeks(count),
+        _ => None,
+    };
+    step.ok_or_else(invalid)
+}
+
+/// Formats a number of seconds in the largest unit of [`parse_step`] that
+/// divides it evenly, falling back to seconds.
+///
+/// # Examples
+///
+/// ```
+/// # use near_balance_monitor::utils::format_step;
+/// assert_eq!(format_step(3600), "1h");
+/// assert_eq!(format_step(90 * 60), "90m");
+/// assert_eq!(format_step(45), "45s");
+/// ```
+pub fn format_step(secs: u64) -> String {
+    let units = [(7 * 86_400, "w"), (86_400, "d"), (3_600, "h"), (60, "m")];
+    for (size, unit) in units {
+        if secs >= size && secs % size == 0 {
+            return format!("{}{}", secs / size, unit);
+        }
+    }
+    format!("{secs}s")
+}
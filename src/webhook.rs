@@ -0,0 +1,407 @@
+//! Outgoing webhooks for balance changes.
+//!
+//! [`WebhookNotifier`] POSTs a [`WebhookPayload`] as JSON to every configured
+//! URL. With a secret, each request is signed with HMAC-SHA256 over
+//! `<timestamp>.<body>`; the Unix timestamp is sent as
+//! `X-Signature-Timestamp` and the signature as `X-Signature-256: sha256=<hex>`,
+//! so receivers can verify the sender and reject replayed requests. Failed
+//! deliveries are retried with backoff (see [`RetryPolicy`]); deliveries that
+//! still fail are appended to a dead-letter file, one JSON object per line,
+//! for inspection or replay.
+//!
+//! Deliveries run in background tasks so that a slow receiver does not delay
+//! balance polling. [`WebhookNotifier::shutdown`] waits for them before the
+//! process exits and dead-letters those that do not finish in time.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::error::Error;
+use crate::retry::{self, RetryPolicy};
+
+/// Header carrying the HMAC-SHA256 signature of `<timestamp>.<body>`.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Header carrying the Unix time (seconds) the request was signed at.
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+
+/// Header carrying the payload ID, which stays the same across retries.
+pub const DELIVERY_HEADER: &str = "X-Webhook-Id";
+
+/// Time limit for one delivery attempt, including reading the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long [`WebhookNotifier::shutdown`] waits for deliveries in flight.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(15);
+
+/// A balance change, as sent to webhooks.
+///
+/// Amounts are strings in the smallest unit (yoctoNEAR, or the token's base
+/// unit for token balances), since JSON numbers cannot hold them exactly.
+#[derive(Clone, Debug, Serialize)]
+pub struct WebhookPayload {
+    /// Unique ID of the change; receivers can use it to drop duplicates.
+    pub id: String,
+    /// Always "balance_change".
+    pub event: &'static str,
+    pub account_id: String,
+    /// Token contract for token balances, `null` for native NEAR.
+    pub token: Option<String>,
+    /// Previously reported balance.
+    pub old: String,
+    /// New balance.
+    pub new: String,
+    /// Signed change, e.g. "-1500000000000000000000000".
+    pub delta: String,
+    /// Time the change was detected (RFC 3339).
+    pub timestamp: String,
+    /// Hashes of the transactions since the previous report, newest first.
+    pub transactions: Vec<String>,
+}
+
+impl WebhookPayload {
+    /// Creates the payload for a change from `old` to `new`.
+    pub fn balance_change(
+        account_id: &str,
+        token: Option<&str>,
+        old: u128,
+        new: u128,
+        transactions: Vec<String>,
+    ) -> Self {
+        let sign = if new < old { "-" } else { "" };
+        Self {
+            id: format!("{:032x}", rand::random::<u128>()),
+            event: "balance_change",
+            account_id: account_id.to_string(),
+            token: token.map(str::to_string),
+            old: old.to_string(),
+            new: new.to_string(),
+            delta: format!("{sign}{}", new.abs_diff(old)),
+            timestamp: Utc::now().to_rfc3339(),
+            transactions,
+        }
+    }
+}
+
+/// One line of the dead-letter file.
+#[derive(Serialize)]
+struct DeadLetter<'a> {
+    /// Time of the last attempt (RFC 3339).
+    failed_at: String,
+    url: &'a str,
+    attempts: u32,
+    /// Error of the last attempt.
+    error: String,
+    payload: &'a WebhookPayload,
+}
+
+/// Sends balance changes to webhook URLs.
+///
+/// Cheap to clone; clones share the HTTP client and configuration.
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    /// Shared configuration.
+    inner: Arc<Inner>,
+}
+
+/// A delivery that has not finished yet.
+struct Pending {
+    url: String,
+    /// Attempts started so far.
+    attempts: u32,
+    payload: Arc<WebhookPayload>,
+}
+
+/// Configuration shared by the clones of a [`WebhookNotifier`].
+struct Inner {
+    /// HTTP client used for deliveries.
+    client: reqwest::Client,
+    /// Receiver URLs.
+    urls: Vec<String>,
+    /// HMAC-SHA256 key; deliveries are unsigned without it.
+    secret: Option<String>,
+    /// Retry behaviour per delivery.
+    retry: RetryPolicy,
+    /// File failed deliveries are appended to.
+    dead_letter: PathBuf,
+    /// Background delivery tasks.
+    tasks: Mutex<JoinSet<()>>,
+    /// Unfinished deliveries by ID, dead-lettered if the process exits first.
+    pending: Mutex<HashMap<u64, Pending>>,
+    /// ID of the next delivery.
+    next_id: AtomicU64,
+}
+
+impl WebhookNotifier {
+    /// Creates a notifier for `urls`, or returns `None` if there are none.
+    pub fn new(
+        urls: Vec<String>,
+        secret: Option<String>,
+        retry: RetryPolicy,
+        dead_letter: PathBuf,
+    ) -> Option<Self> {
+        if urls.is_empty() {
+            return None;
+        }
+        log::info!(
+            "Webhooks configured urls={} signed={} attempts={} dead_letter={}",
+            urls.len(),
+            secret.is_some(),
+            retry.max_attempts,
+            dead_letter.display()
+        );
+        Some(Self {
+            inner: Arc::new(Inner {
+                client: reqwest::Client::builder()
+                    .timeout(REQUEST_TIMEOUT)
+                    .build()
+                    .unwrap_or_else(|e| {
+                        log::warn!("Failed to build webhook HTTP client: {}", e);
+                        reqwest::Client::new()
+                    }),
+                urls,
+                secret,
+                retry,
+                dead_letter,
+                tasks: Mutex::new(JoinSet::new()),
+                pending: Mutex::new(HashMap::new()),
+                next_id: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// Delivers `payload` to every URL in the background.
+    pub fn send(&self, payload: WebhookPayload) {
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!(
+                    "Failed to serialize webhook payload id={}: {}",
+                    payload.id,
+                    e
+                );
+                return;
+            }
+        };
+        let payload = Arc::new(payload);
+        let mut tasks = self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        // Reap finished tasks so the set does not grow without bound.
+        while tasks.try_join_next().is_some() {}
+        for url in &self.inner.urls {
+            let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+            self.inner.pending().insert(
+                id,
+                Pending {
+                    url: url.clone(),
+                    attempts: 0,
+                    payload: payload.clone(),
+                },
+            );
+            let inner = self.inner.clone();
+            let url = url.clone();
+            let body = body.clone();
+            let payload = payload.clone();
+            tasks.spawn(async move {
+                inner.deliver(id, &url, &body, &payload).await;
+                inner.pending().remove(&id);
+            });
+        }
+    }
+
+    /// Waits for deliveries in flight, for up to [`SHUTDOWN_GRACE`], and
+    /// dead-letters those that have not finished by then.
+    ///
+    /// Call before the process exits; deliveries still running when the
+    /// runtime shuts down would otherwise be lost without a trace.
+    pub async fn shutdown(&self) {
+        let mut tasks =
+            std::mem::take(&mut *self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner()));
+        if tasks.is_empty() {
+            return;
+        }
+        log::info!(
+            "Waiting for webhook deliveries tasks={} grace_secs={}",
+            tasks.len(),
+            SHUTDOWN_GRACE.as_secs()
+        );
+        let drained = tokio::time::timeout(SHUTDOWN_GRACE, async {
+            while tasks.join_next().await.is_some() {}
+        })
+        .await;
+        if drained.is_ok() {
+            return;
+        }
+        tasks.abort_all();
+        while tasks.join_next().await.is_some() {}
+        let pending = std::mem::take(&mut *self.inner.pending());
+        for delivery in pending.into_values() {
+            log::error!(
+                "Webhook delivery unfinished at shutdown url={} id={} attempts={}",
+                delivery.url,
+                delivery.payload.id,
+                delivery.attempts
+            );
+            self.inner.dead_letter(
+                &delivery.url,
+                delivery.attempts,
+                "process exited before the delivery completed",
+                &delivery.payload,
+            );
+        }
+    }
+}
+
+impl Inner {
+    /// POSTs `body` to `url`, retrying transient failures, and records the
+    /// delivery in the dead-letter file if all attempts fail.
+    ///
+    /// `id` identifies the delivery in the pending map, where the attempt
+    /// count is kept up to date.
+    async fn deliver(&self, id: u64, url: &str, body: &str, payload: &WebhookPayload) {
+        let mut attempt = 1;
+        loop {
+            if let Some(delivery) = self.pending().get_mut(&id) {
+                delivery.attempts = attempt;
+            }
+            match self.post(url, body, &payload.id).await {
+                Ok(()) => {
+                    log::info!(
+                        "Webhook delivered url={} id={} account={} attempt={}",
+                        url,
+                        payload.id,
+                        payload.account_id,
+                        attempt
+                    );
+                    return;
+                }
+                Err((e, retryable)) => {
+                    if !retryable || !self.retry.should_retry(attempt) {
+                        log::error!(
+                            "Webhook delivery failed url={} id={} attempts={} kind={}: {}",
+                            url,
+                            payload.id,
+                            attempt,
+                            e.kind(),
+                            e
+                        );
+                        self.dead_letter(url, attempt, &e.to_string(), payload);
+                        return;
+                    }
+                    let delay = self.retry.delay_for(attempt);
+                    log::warn!(
+                        "Webhook attempt failed url={} id={} attempt={}/{} retry_in_ms={}: {}",
+                        url,
+                        payload.id,
+                        attempt,
+                        self.retry.max_attempts,
+                        delay.as_millis(),
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the pending deliveries.
+    fn pending(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Pending>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Sends one request, signed at the current time if a secret is set; the
+    /// error says whether a retry may succeed.
+    async fn post(&self, url: &str, body: &str, id: &str) -> Result<(), (Error, bool)> {
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(DELIVERY_HEADER, id)
+            .body(body.to_string());
+        if let Some(secret) = &self.secret {
+            let timestamp = Utc::now().timestamp().to_string();
+            let signature = sign(secret, &timestamp, body).map_err(|e| (e, false))?;
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, signature);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| (Error::Transport(e.to_string()), true))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err((
+                Error::HttpStatus {
+                    status: status.as_u16(),
+                    url: url.to_string(),
+                },
+                retry::is_retryable_status(status),
+            ))
+        }
+    }
+
+    /// Appends a failed delivery to the dead-letter file.
+    fn dead_letter(&self, url: &str, attempts: u32, error: &str, payload: &WebhookPayload) {
+        let entry = DeadLetter {
+            failed_at: Utc::now().to_rfc3339(),
+            url,
+            attempts,
+            error: error.to_string(),
+            payload,
+        };
+        if let Err(e) = append_line(&self.dead_letter, &entry) {
+            log::error!(
+                "Failed to write webhook dead letter path={} id={}: {}",
+                self.dead_letter.display(),
+                payload.id,
+                e
+            );
+        }
+    }
+}
+
+/// Appends `entry` as one JSON line to `path`.
+fn append_line(path: &Path, entry: &impl Serialize) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Returns the signature header value for a request signed at `timestamp`:
+/// `sha256=` followed by the hex-encoded HMAC-SHA256 of `<timestamp>.<body>`
+/// under `secret`. Covering the timestamp lets receivers reject replays.
+///
+/// # Errors
+///
+/// Returns a `Config` error if the secret is not a valid key (HMAC accepts
+/// keys of any length, so this does not happen in practice).
+fn sign(secret: &str, timestamp: &str, body: &str) -> Result<String, Error> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| Error::Config(format!("invalid webhook secret: {e}")))?;
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    Ok(format!("sha256={hex}"))
+}
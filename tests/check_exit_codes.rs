@@ -0,0 +1,65 @@
+//! `check` must exit 3 (UNKNOWN) for anything that is not a probe result,
+//! since monitoring systems read other codes, notably clap's usage error 2,
+//! as a balance status.
+
+use std::process::Command;
+
+/// Nagios plugin exit code for UNKNOWN.
+const UNKNOWN: i32 = 3;
+
+/// Runs the binary with `args` and returns its exit code and stdout.
+fn run(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_app"))
+        .args(args)
+        .env_remove("NEAR_NETWORK")
+        .env_remove("NEAR_RPC_URL")
+        .env_remove("NEAR_PRICE_FILE")
+        .output()
+        .expect("failed to run the binary");
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+    )
+}
+
+#[test]
+fn usage_errors_are_unknown() {
+    for args in [
+        &["check"][..],
+        &["check", "example.near", "--warn-below", "abc"],
+        &["check", "example.near", "--no-such-flag"],
+    ] {
+        let (code, stdout) = run(args);
+        assert_eq!(code, Some(UNKNOWN), "args {args:?}");
+        assert!(
+            stdout.starts_with("NEAR UNKNOWN - "),
+            "args {args:?}: {stdout}"
+        );
+    }
+}
+
+#[test]
+fn setup_errors_are_unknown() {
+    for args in [
+        &["--network", "custom", "check", "example.near"][..],
+        &[
+            "--price-file",
+            "/nonexistent/prices.json",
+            "check",
+            "example.near",
+        ],
+    ] {
+        let (code, stdout) = run(args);
+        assert_eq!(code, Some(UNKNOWN), "args {args:?}");
+        assert!(
+            stdout.starts_with("NEAR UNKNOWN - "),
+            "args {args:?}: {stdout}"
+        );
+    }
+}
+
+#[test]
+fn other_commands_keep_usage_exit_code() {
+    let (code, _) = run(&["balance"]);
+    assert_eq!(code, Some(2));
+}